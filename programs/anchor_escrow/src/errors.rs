@@ -10,4 +10,114 @@ pub enum EscrowError {
   InvalidMintA,
   #[msg("Invalid mint b")]
   InvalidMintB,
+  #[msg("Escrow kind does not match instruction")]
+  InvalidEscrowKind,
+  #[msg("Escrow delegate allowance is insufficient")]
+  InsufficientAllowance,
+  #[msg("Maker token balance is insufficient")]
+  InsufficientBalance,
+  #[msg("Signed order has expired")]
+  OrderExpired,
+  #[msg("Signed order nonce was already used")]
+  OrderAlreadyFilled,
+  #[msg("Missing or malformed Ed25519 signature instruction")]
+  InvalidSignature,
+  #[msg("Price feed account does not match the escrow oracle")]
+  InvalidPriceFeed,
+  #[msg("Oracle price must be positive")]
+  InvalidOraclePrice,
+  #[msg("Oracle price is stale")]
+  StalePrice,
+  #[msg("Oracle confidence interval is too wide")]
+  PriceConfidenceTooWide,
+  #[msg("Arithmetic overflow")]
+  MathOverflow,
+  #[msg("Price numerator and denominator must be positive")]
+  InvalidPrice,
+  #[msg("Vault holds no tokens beyond the escrowed amount")]
+  NoExcess,
+  #[msg("Escrow is already filled")]
+  EscrowAlreadyFilled,
+  #[msg("Escrow has been cancelled")]
+  EscrowCancelled,
+  #[msg("Escrow cannot move to that status")]
+  InvalidStatusTransition,
+  #[msg("Escrow is already at the current layout version")]
+  EscrowAlreadyMigrated,
+  #[msg("Escrow account layout is not recognised")]
+  UnsupportedEscrowVersion,
+  #[msg("Escrow extension data is malformed")]
+  InvalidExtensionData,
+  #[msg("Extension listed more than once")]
+  DuplicateExtension,
+  #[msg("Memo is too long")]
+  MemoTooLong,
+  #[msg("Taker is not allowed to fill this escrow")]
+  TakerNotAllowed,
+  #[msg("Maker has too many open escrows")]
+  TooManyOpenEscrows,
+  #[msg("Making escrows is paused")]
+  MakePaused,
+  #[msg("Taking escrows is paused")]
+  TakePaused,
+  #[msg("Signer is not the config admin")]
+  Unauthorized,
+  #[msg("Signer is not the pending admin")]
+  NotPendingAdmin,
+  #[msg("Mint is not allowed by the mint policy")]
+  MintNotAllowed,
+  #[msg("Mint has a permanent delegate")]
+  MintHasPermanentDelegate,
+  #[msg("Mint is non-transferable")]
+  MintNonTransferable,
+  #[msg("Mint freezes new accounts by default")]
+  MintDefaultFrozen,
+  #[msg("Mint has a freeze authority")]
+  MintHasFreezeAuthority,
+  #[msg("Mint UI amount configuration is invalid")]
+  InvalidUiAmountConfig,
+  #[msg("Token account is not the escrow's proceeds destination")]
+  InvalidProceedsDestination,
+  #[msg("No token account was passed to receive the transfer")]
+  MissingTokenAccount,
+  #[msg("Proceeds split must name 1-8 distinct accounts with shares summing to 10,000 bps")]
+  InvalidProceedsSplit,
+  #[msg("Token account does not match the escrow's proceeds split")]
+  InvalidSplitRecipient,
+  #[msg("Referral fee exceeds the configured cap")]
+  ReferralFeeTooHigh,
+  #[msg("Mint is not an NFT: it needs 0 decimals and a supply of 1")]
+  NotAnNft,
+  #[msg("NFT royalty metadata is malformed")]
+  InvalidRoyaltyMetadata,
+  #[msg("Token account does not belong to the NFT's royalty creator")]
+  InvalidRoyaltyRecipient,
+  #[msg("NFT is not a verified member of the collection")]
+  NotInCollection,
+  #[msg("Escrow is not a collection offer")]
+  NotACollectionOffer,
+  #[msg("Escrow is token-gated: pass the taker's token account of the gate mint")]
+  TokenGateMissing,
+  #[msg("Taker holds less of the gate mint than the escrow requires")]
+  TokenGateInsufficient,
+  #[msg("Escrow's gate requires a non-transferable membership mint")]
+  TokenGateTransferable,
+  #[msg("Taker is not on the escrow's allowlist")]
+  NotAllowlisted,
+  #[msg("Take would exceed the taker's allowlist cap")]
+  TakerCapExceeded,
+  #[msg("Capped allowlist takes need the taker_fill account")]
+  MissingTakerFill,
+  #[msg("Oracle max_staleness must be above zero")]
+  InvalidOracleStaleness,
+  #[msg("Oracle max_confidence_bps must be above zero")]
+  InvalidOracleConfidence,
+  #[msg("Seed is not the maker's next_seed")]
+  SeedMismatch,
+  #[msg("Oracle price is not trading")]
+  OraclePriceNotTrading,
+  #[msg("NFTs must be escrowed with make_nft so their creators are paid")]
+  NftNeedsMakeNft,
+  #[msg("SPL Token NFTs need their Token Metadata account")]
+  MissingNftMetadata,
 }
\ No newline at end of file
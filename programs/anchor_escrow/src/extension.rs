@@ -0,0 +1,581 @@
+use crate::errors::EscrowError;
+use crate::state::{realloc_account, Escrow, EscrowKind};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        non_transferable::NonTransferableAccount, BaseStateWithExtensions, StateWithExtensions,
+    },
+};
+
+/// Optional escrow features live in a TLV region after the fixed `Escrow`
+/// layout, Token-2022 style: `type: u16 | length: u16 | value`, repeated.
+/// A type of 0 (zero padding) ends the region.
+pub const EXTENSIONS_OFFSET: usize = Escrow::LEN;
+const HEADER_LEN: usize = 4;
+pub const MAX_MEMO_LEN: usize = 128;
+pub const MAX_SPLIT_RECIPIENTS: usize = 8;
+/// Metaplex's limit, kept for Token-2022 metadata too.
+pub const MAX_CREATORS: usize = 5;
+const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum ExtensionType {
+    Expiry = 1,
+    AllowedTaker = 2,
+    Memo = 3,
+    UiReceive = 4,
+    ProceedsDestination = 5,
+    ProceedsSplit = 6,
+    NftRoyalties = 7,
+    CollectionOffer = 8,
+    TokenGate = 9,
+    MerkleAllowlist = 10,
+}
+
+/// A value that can be stored in the extension region.
+pub trait Extension: AnchorSerialize + AnchorDeserialize {
+    const TYPE: ExtensionType;
+}
+
+/// Takes are rejected after `unix_timestamp`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct Expiry {
+    pub unix_timestamp: i64,
+}
+
+/// Only `taker` may fill the escrow.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct AllowedTaker {
+    pub taker: Pubkey,
+}
+
+/// Free-form maker note, at most `MAX_MEMO_LEN` bytes.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct Memo {
+    pub memo: String,
+}
+
+/// Takes pay mint_b into `token_account` instead of the maker's ATA. It is
+/// checked against mint_b at take time, so a wrong address only blocks takes
+/// until the maker replaces it. Vault escrows only: `take_delegated` always
+/// pays the maker's ATA.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ProceedsDestination {
+    pub token_account: Pubkey,
+}
+
+/// Takers must hold at least `min_balance` of `mint`. With
+/// `non_transferable`, the gate mint must be a Token-2022 non-transferable
+/// (membership) mint, so one membership cannot be lent to many takers.
+/// Vault escrows only: `take_delegated` passes no gate account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct TokenGate {
+    pub mint: Pubkey,
+    pub min_balance: u64,
+    pub non_transferable: bool,
+}
+
+/// Only takers with a proof against `root` may fill. Leaves are
+/// `sha256(0x00 || taker)`, or `sha256(0x00 || taker || cap)` to limit that
+/// taker to `cap` of mint_a across fills; nodes are `sha256(0x01 || lo || hi)`
+/// over the sorted pair. Vault escrows only: `take_delegated` takes no proof.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct MerkleAllowlist {
+    pub root: [u8; 32],
+}
+
+impl MerkleAllowlist {
+    /// Whether `proof` places `(taker, cap)` under `root`.
+    pub fn verify(&self, taker: &Pubkey, cap: Option<u64>, proof: &[[u8; 32]]) -> bool {
+        let mut node = allowlist_leaf(taker, cap);
+        for sibling in proof {
+            let (lo, hi) = if node <= *sibling {
+                (&node, sibling)
+            } else {
+                (sibling, &node)
+            };
+            node = hashv(&[&[1], lo, hi]).to_bytes();
+        }
+        node == self.root
+    }
+}
+
+fn allowlist_leaf(taker: &Pubkey, cap: Option<u64>) -> [u8; 32] {
+    match cap {
+        Some(cap) => hashv(&[&[0], taker.as_ref(), &cap.to_le_bytes()]),
+        None => hashv(&[&[0], taker.as_ref()]),
+    }
+    .to_bytes()
+}
+
+/// A taker's claim to a `MerkleAllowlist` entry, passed to `take`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct AllowlistProof {
+    pub proof: Vec<[u8; 32]>,
+    /// The taker's cap, if their leaf carries one.
+    pub cap: Option<u64>,
+}
+
+/// One party's share of a `ProceedsSplit`, in basis points.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SplitRecipient {
+    pub token_account: Pubkey,
+    pub bps: u16,
+}
+
+/// Takes pay mint_b across up to `MAX_SPLIT_RECIPIENTS` token accounts whose
+/// shares sum to 10,000 bps. Takes with a split ignore `ProceedsDestination`.
+/// Vault escrows only, like `ProceedsDestination`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ProceedsSplit {
+    pub recipients: Vec<SplitRecipient>,
+}
+
+impl ProceedsSplit {
+    fn validate(&self) -> Result<()> {
+        require!(
+            !self.recipients.is_empty() && self.recipients.len() <= MAX_SPLIT_RECIPIENTS,
+            EscrowError::InvalidProceedsSplit
+        );
+        let mut total = 0u64;
+        for (i, recipient) in self.recipients.iter().enumerate() {
+            require!(
+                recipient.bps > 0
+                    && self.recipients[..i]
+                        .iter()
+                        .all(|r| r.token_account != recipient.token_account),
+                EscrowError::InvalidProceedsSplit
+            );
+            total += recipient.bps as u64;
+        }
+        require_eq!(total, BPS_DENOMINATOR, EscrowError::InvalidProceedsSplit);
+        Ok(())
+    }
+
+    /// Each recipient's part of `amount`; see `pro_rata`.
+    pub fn shares(&self, amount: u64) -> Vec<u64> {
+        let weights = self.recipients.iter().map(|r| r.bps as u64);
+        pro_rata(amount, weights, BPS_DENOMINATOR)
+    }
+}
+
+/// A creator owed part of an NFT's royalties, with `share` in percent.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct RoyaltyCreator {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+/// Royalties read from the NFT's metadata by `make_nft`: `royalty_bps` of
+/// each payment goes to `creators` by share, out of what the maker receives.
+/// Written by the program only, so it is not an `EscrowExtension`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Default)]
+pub struct NftRoyalties {
+    pub royalty_bps: u16,
+    pub creators: Vec<RoyaltyCreator>,
+}
+
+impl NftRoyalties {
+    pub(crate) fn validate(&self) -> Result<()> {
+        require_gte!(
+            BPS_DENOMINATOR,
+            self.royalty_bps as u64,
+            EscrowError::InvalidRoyaltyMetadata
+        );
+        require_gte!(MAX_CREATORS, self.creators.len(), EscrowError::InvalidRoyaltyMetadata);
+        if self.royalty_bps > 0 {
+            let total: u64 = self.creators.iter().map(|c| c.share as u64).sum();
+            require_eq!(total, 100, EscrowError::InvalidRoyaltyMetadata);
+        }
+        Ok(())
+    }
+
+    /// Royalty on `payment`, rounded down.
+    pub fn royalty(&self, payment: u64) -> u64 {
+        (payment as u128 * self.royalty_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    /// Each creator's part of `royalty`; see `pro_rata`.
+    pub fn shares(&self, royalty: u64) -> Vec<u64> {
+        pro_rata(royalty, self.creators.iter().map(|c| c.share as u64), 100)
+    }
+}
+
+/// Splits `amount` by `weights` out of `total`, rounding each part down and
+/// adding the remainder to the first so the parts always sum to `amount`.
+fn pro_rata(amount: u64, weights: impl Iterator<Item = u64>, total: u64) -> Vec<u64> {
+    let mut parts: Vec<u64> = weights
+        .map(|w| (amount as u128 * w as u128 / total as u128) as u64)
+        .collect();
+    let paid: u64 = parts.iter().sum();
+    if let Some(first) = parts.first_mut() {
+        *first += amount - paid;
+    }
+    parts
+}
+
+/// mint_b is a collection rather than a mint: each fill delivers any one NFT
+/// from it, chosen by the taker, and `receive` counts the NFTs wanted.
+/// Written by `make_collection_bid` only.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CollectionOffer {}
+
+/// `receive` as the maker entered it in `make_ui_amount`: mint_b UI units
+/// times 10^decimals, converted to raw at `converted_at`. Written by the
+/// program only, so it is not an `EscrowExtension`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct UiReceive {
+    pub receive_ui: u64,
+    pub converted_at: i64,
+}
+
+impl Extension for Expiry {
+    const TYPE: ExtensionType = ExtensionType::Expiry;
+}
+
+impl Extension for AllowedTaker {
+    const TYPE: ExtensionType = ExtensionType::AllowedTaker;
+}
+
+impl Extension for Memo {
+    const TYPE: ExtensionType = ExtensionType::Memo;
+}
+
+impl Extension for ProceedsDestination {
+    const TYPE: ExtensionType = ExtensionType::ProceedsDestination;
+}
+
+impl Extension for TokenGate {
+    const TYPE: ExtensionType = ExtensionType::TokenGate;
+}
+
+impl Extension for MerkleAllowlist {
+    const TYPE: ExtensionType = ExtensionType::MerkleAllowlist;
+}
+
+impl Extension for ProceedsSplit {
+    const TYPE: ExtensionType = ExtensionType::ProceedsSplit;
+}
+
+impl Extension for NftRoyalties {
+    const TYPE: ExtensionType = ExtensionType::NftRoyalties;
+}
+
+impl Extension for CollectionOffer {
+    const TYPE: ExtensionType = ExtensionType::CollectionOffer;
+}
+
+impl Extension for UiReceive {
+    const TYPE: ExtensionType = ExtensionType::UiReceive;
+}
+
+/// Extension values as passed to `make` and `add_extensions`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum EscrowExtension {
+    Expiry(Expiry),
+    AllowedTaker(AllowedTaker),
+    Memo(Memo),
+    ProceedsDestination(ProceedsDestination),
+    ProceedsSplit(ProceedsSplit),
+    TokenGate(TokenGate),
+    MerkleAllowlist(MerkleAllowlist),
+}
+
+impl EscrowExtension {
+    /// Whether takes of a `kind` escrow honour this extension.
+    pub fn supports(&self, kind: EscrowKind) -> bool {
+        !matches!(
+            (self, kind),
+            (
+                Self::ProceedsDestination(_)
+                    | Self::ProceedsSplit(_)
+                    | Self::TokenGate(_)
+                    | Self::MerkleAllowlist(_),
+                EscrowKind::Delegated
+            )
+        )
+    }
+    fn extension_type(&self) -> ExtensionType {
+        match self {
+            Self::Expiry(_) => Expiry::TYPE,
+            Self::AllowedTaker(_) => AllowedTaker::TYPE,
+            Self::Memo(_) => Memo::TYPE,
+            Self::ProceedsDestination(_) => ProceedsDestination::TYPE,
+            Self::ProceedsSplit(_) => ProceedsSplit::TYPE,
+            Self::TokenGate(_) => TokenGate::TYPE,
+            Self::MerkleAllowlist(_) => MerkleAllowlist::TYPE,
+        }
+    }
+    fn value(&self) -> Result<Vec<u8>> {
+        let value = match self {
+            Self::Expiry(expiry) => expiry.try_to_vec()?,
+            Self::AllowedTaker(allowed) => allowed.try_to_vec()?,
+            Self::Memo(memo) => {
+                require_gte!(MAX_MEMO_LEN, memo.memo.len(), EscrowError::MemoTooLong);
+                memo.try_to_vec()?
+            }
+            Self::ProceedsDestination(destination) => destination.try_to_vec()?,
+            Self::ProceedsSplit(split) => {
+                split.validate()?;
+                split.try_to_vec()?
+            }
+            Self::TokenGate(gate) => gate.try_to_vec()?,
+            Self::MerkleAllowlist(allowlist) => allowlist.try_to_vec()?,
+        };
+        Ok(value)
+    }
+}
+
+/// Splits a TLV region into `(type, value)` entries.
+fn entries(tlv: &[u8]) -> Result<Vec<(u16, &[u8])>> {
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while at + HEADER_LEN <= tlv.len() {
+        let ty = u16::from_le_bytes([tlv[at], tlv[at + 1]]);
+        if ty == 0 {
+            break;
+        }
+        let len = u16::from_le_bytes([tlv[at + 2], tlv[at + 3]]) as usize;
+        let value = tlv
+            .get(at + HEADER_LEN..at + HEADER_LEN + len)
+            .ok_or(EscrowError::InvalidExtensionData)?;
+        entries.push((ty, value));
+        at += HEADER_LEN + len;
+    }
+    Ok(entries)
+}
+
+/// Reads extension `T` from an escrow account, if the maker set one.
+pub fn get_extension<T: Extension>(escrow: &AccountInfo) -> Result<Option<T>> {
+    let data = escrow.try_borrow_data()?;
+    let tlv = data.get(EXTENSIONS_OFFSET..).unwrap_or_default();
+    for (ty, value) in entries(tlv)? {
+        if ty == T::TYPE as u16 {
+            let extension =
+                T::try_from_slice(value).map_err(|_| EscrowError::InvalidExtensionData)?;
+            return Ok(Some(extension));
+        }
+    }
+    Ok(None)
+}
+
+/// Writes `extensions` into the escrow, replacing any already set with the
+/// same type, and resizes the account to fit. `payer` covers extra rent.
+pub fn set_extensions<'info>(
+    escrow: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    extensions: &[EscrowExtension],
+) -> Result<()> {
+    let mut updates = Vec::with_capacity(extensions.len());
+    for (i, extension) in extensions.iter().enumerate() {
+        let ty = extension.extension_type();
+        require!(
+            extensions[..i].iter().all(|e| e.extension_type() != ty),
+            EscrowError::DuplicateExtension
+        );
+        updates.push((ty as u16, extension.value()?));
+    }
+    write_entries(escrow, payer, system_program, updates)
+}
+
+/// Writes one extension the program manages itself, like `set_extensions`.
+pub(crate) fn set_extension<'info, T: Extension>(
+    escrow: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    extension: &T,
+) -> Result<()> {
+    let updates = vec![(T::TYPE as u16, extension.try_to_vec()?)];
+    write_entries(escrow, payer, system_program, updates)
+}
+
+/// Merges `updates` into the existing entries and rewrites the region.
+fn write_entries<'info>(
+    escrow: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    updates: Vec<(u16, Vec<u8>)>,
+) -> Result<()> {
+    let mut merged: Vec<(u16, Vec<u8>)> = {
+        let data = escrow.try_borrow_data()?;
+        let tlv = data.get(EXTENSIONS_OFFSET..).unwrap_or_default();
+        entries(tlv)?
+            .into_iter()
+            .map(|(ty, value)| (ty, value.to_vec()))
+            .collect()
+    };
+
+    for (ty, value) in updates {
+        match merged.iter_mut().find(|(t, _)| *t == ty) {
+            Some(entry) => entry.1 = value,
+            None => merged.push((ty, value)),
+        }
+    }
+
+    let tlv_len: usize = merged.iter().map(|(_, v)| HEADER_LEN + v.len()).sum();
+    realloc_account(escrow, payer, system_program, EXTENSIONS_OFFSET + tlv_len)?;
+
+    let mut data = escrow.try_borrow_mut_data()?;
+    let mut at = EXTENSIONS_OFFSET;
+    for (ty, value) in merged {
+        let len = u16::try_from(value.len()).map_err(|_| EscrowError::InvalidExtensionData)?;
+        data[at..at + 2].copy_from_slice(&ty.to_le_bytes());
+        data[at + 2..at + 4].copy_from_slice(&len.to_le_bytes());
+        data[at + HEADER_LEN..at + HEADER_LEN + value.len()].copy_from_slice(&value);
+        at += HEADER_LEN + value.len();
+    }
+    Ok(())
+}
+
+/// Enforces the `Expiry`, `AllowedTaker`, `TokenGate` and `MerkleAllowlist`
+/// extensions for a take. `taker_gate` is the taker's token account of the
+/// gate mint and `allowlist` their proof, if any. Per-taker caps are left to
+/// the caller.
+pub fn check_take(
+    escrow: &AccountInfo,
+    taker: &Pubkey,
+    now: i64,
+    taker_gate: Option<&AccountInfo>,
+    allowlist: Option<&AllowlistProof>,
+) -> Result<()> {
+    if let Some(expiry) = get_extension::<Expiry>(escrow)? {
+        require_gte!(expiry.unix_timestamp, now, EscrowError::OrderExpired);
+    }
+    if let Some(allowed) = get_extension::<AllowedTaker>(escrow)? {
+        require_keys_eq!(allowed.taker, *taker, EscrowError::TakerNotAllowed);
+    }
+    if let Some(gate) = get_extension::<TokenGate>(escrow)? {
+        let account = taker_gate.ok_or(EscrowError::TokenGateMissing)?;
+        check_gate(&gate, account, taker)?;
+    }
+    if let Some(merkle) = get_extension::<MerkleAllowlist>(escrow)? {
+        let claim = allowlist.ok_or(EscrowError::NotAllowlisted)?;
+        require!(
+            merkle.verify(taker, claim.cap, &claim.proof),
+            EscrowError::NotAllowlisted
+        );
+    }
+    Ok(())
+}
+
+fn check_gate(gate: &TokenGate, account: &AccountInfo, taker: &Pubkey) -> Result<()> {
+    require!(
+        account.owner == &spl_token::ID || account.owner == &spl_token_2022::ID,
+        EscrowError::TokenGateMissing
+    );
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    require!(
+        state.base.mint == gate.mint && state.base.owner == *taker,
+        EscrowError::TokenGateMissing
+    );
+    require_gte!(
+        state.base.amount,
+        gate.min_balance,
+        EscrowError::TokenGateInsufficient
+    );
+    if gate.non_transferable {
+        // Token-2022 marks every account of a non-transferable mint
+        require!(
+            state.get_extension::<NonTransferableAccount>().is_ok(),
+            EscrowError::TokenGateTransferable
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ty: ExtensionType, value: &[u8]) -> Vec<u8> {
+        let mut bytes = (ty as u16).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(value);
+        bytes
+    }
+
+    #[test]
+    fn entries_stop_at_zero_padding() {
+        let mut tlv = entry(ExtensionType::Expiry, &42i64.to_le_bytes());
+        tlv.extend(entry(ExtensionType::Memo, b"\x02\x00\x00\x00hi"));
+        tlv.extend([0u8; 6]);
+
+        let parsed = entries(&tlv).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0], (1, &42i64.to_le_bytes()[..]));
+        assert_eq!(Memo::try_from_slice(parsed[1].1).unwrap().memo, "hi");
+    }
+
+    #[test]
+    fn entries_reject_truncated_value() {
+        let mut tlv = entry(ExtensionType::AllowedTaker, &[7u8; 32]);
+        tlv.truncate(20);
+
+        assert_eq!(
+            entries(&tlv).unwrap_err(),
+            EscrowError::InvalidExtensionData.into()
+        );
+    }
+
+    #[test]
+    fn memo_length_is_capped() {
+        let memo = EscrowExtension::Memo(Memo { memo: "x".repeat(MAX_MEMO_LEN + 1) });
+        assert_eq!(memo.value().unwrap_err(), EscrowError::MemoTooLong.into());
+    }
+
+    fn split(bps: &[u16]) -> ProceedsSplit {
+        ProceedsSplit {
+            recipients: bps
+                .iter()
+                .map(|&bps| SplitRecipient { token_account: Pubkey::new_unique(), bps })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn split_shares_conserve_amount() {
+        let split = split(&[3_333, 3_333, 3_334]);
+        for amount in [0, 1, 2, 999, 1_000, 12_345, u64::MAX] {
+            let shares = split.shares(amount);
+            assert_eq!(shares.iter().map(|&s| s as u128).sum::<u128>(), amount as u128);
+        }
+        // 1,000 splits 333/333/333 with the leftover 1 to the first
+        assert_eq!(split.shares(1_000), vec![334, 333, 333]);
+    }
+
+    #[test]
+    fn split_must_cover_exactly_10k_bps() {
+        assert!(split(&[5_000, 5_000]).validate().is_ok());
+        for bad in [&[5_000, 4_999][..], &[10_000, 0], &[], &[1_250; 9]] {
+            assert_eq!(
+                split(bad).validate().unwrap_err(),
+                EscrowError::InvalidProceedsSplit.into()
+            );
+        }
+    }
+
+    #[test]
+    fn allowlist_proof_binds_taker_and_cap() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (leaf_a, leaf_b) = (allowlist_leaf(&a, Some(500)), allowlist_leaf(&b, None));
+        let leaf_c = allowlist_leaf(&c, None);
+        let node = |x: [u8; 32], y: [u8; 32]| {
+            let (lo, hi) = if x <= y { (x, y) } else { (y, x) };
+            hashv(&[&[1], &lo, &hi]).to_bytes()
+        };
+        let allowlist = MerkleAllowlist { root: node(node(leaf_a, leaf_b), leaf_c) };
+
+        assert!(allowlist.verify(&a, Some(500), &[leaf_b, leaf_c]));
+        assert!(allowlist.verify(&c, None, &[node(leaf_a, leaf_b)]));
+        // A raised cap, a missing cap or someone else's proof all fail
+        assert!(!allowlist.verify(&a, Some(501), &[leaf_b, leaf_c]));
+        assert!(!allowlist.verify(&a, None, &[leaf_b, leaf_c]));
+        assert!(!allowlist.verify(&Pubkey::new_unique(), None, &[node(leaf_a, leaf_b)]));
+    }
+}
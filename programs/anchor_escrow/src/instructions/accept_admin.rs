@@ -0,0 +1,24 @@
+use crate::{errors::EscrowError, state::Config};
+use anchor_lang::prelude::*;
+
+/// Second half of an admin rotation, signed by the proposed admin so a typo
+/// in `propose_admin` cannot hand the config to a key nobody holds.
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(new_admin.key()) @ EscrowError::NotPendingAdmin,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.new_admin.key();
+    config.pending_admin = None;
+    Ok(())
+}
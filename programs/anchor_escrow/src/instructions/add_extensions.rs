@@ -0,0 +1,38 @@
+use crate::{
+    errors::EscrowError,
+    extension::{set_extensions, EscrowExtension},
+    state::Escrow,
+};
+use anchor_lang::prelude::*;
+
+/// Sets or replaces extensions on an existing escrow, growing the account
+/// as needed at the maker's expense.
+#[derive(Accounts)]
+pub struct AddExtensions<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddExtensions>, extensions: Vec<EscrowExtension>) -> Result<()> {
+    let kind = ctx.accounts.escrow.kind;
+    require!(
+        extensions.iter().all(|extension| extension.supports(kind)),
+        EscrowError::InvalidEscrowKind
+    );
+    set_extensions(
+        &ctx.accounts.escrow.to_account_info(),
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &extensions,
+    )
+}
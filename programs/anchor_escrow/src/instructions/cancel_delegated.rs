@@ -0,0 +1,87 @@
+use crate::{
+    errors::EscrowError,
+    state::{Escrow, EscrowKind, EscrowStatus, MakerState, Registry},
+};
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token_interface::{revoke, Mint, Revoke, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CancelDelegated<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = escrow.kind == EscrowKind::Delegated @ EscrowError::InvalidEscrowKind,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        mut,
+        seeds = [b"pair_registry", mint_a.key().as_ref(), escrow.mint_b.as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        mut,
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump = maker_registry.bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+}
+
+impl<'info> CancelDelegated<'info> {
+    /// Only revoke the approval this escrow owns; the maker may have since
+    /// delegated `maker_ata_a` to someone else.
+    fn revoke_escrow(&mut self) -> Result<()> {
+        if self.maker_ata_a.delegate != COption::Some(self.escrow.key()) {
+            return Ok(());
+        }
+
+        revoke(CpiContext::new(
+            self.token_program.to_account_info(),
+            Revoke {
+                source: self.maker_ata_a.to_account_info(),
+                authority: self.maker.to_account_info(),
+            },
+        ))?;
+
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CancelDelegated>) -> Result<()> {
+    ctx.accounts
+        .escrow
+        .transition(EscrowStatus::Cancelled, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.revoke_escrow()?;
+    ctx.accounts.maker_state.close_escrow();
+    let escrow = ctx.accounts.escrow.key();
+    ctx.accounts.pair_registry.remove(&escrow);
+    ctx.accounts.maker_registry.remove(&escrow);
+
+    Ok(())
+}
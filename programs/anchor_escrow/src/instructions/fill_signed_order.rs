@@ -0,0 +1,251 @@
+use crate::errors::EscrowError;
+use crate::nft::check_not_nft;
+use crate::state::{Config, OrderNonce};
+use crate::token_ext::check_mint_hazards;
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        ed25519_program,
+        program_option::COption,
+        sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+/// Order terms the maker signs off-chain. Nothing is stored on-chain until a
+/// taker fills it; mint_a moves through the maker's approval of the
+/// `order_authority` PDA.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SignedOrder {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount: u64,
+    pub receive: u64,
+    pub nonce: u64,
+    pub expiry: i64,
+}
+
+impl SignedOrder {
+    /// Bytes the maker signs: the program id followed by the borsh encoded
+    /// order, so a signature cannot be replayed against another deployment.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = crate::ID.to_bytes().to_vec();
+        message.extend_from_slice(&borsh::to_vec(self)?);
+        Ok(message)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(order: SignedOrder)]
+pub struct FillSignedOrder<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(address = order.maker @ EscrowError::InvalidMaker)]
+    pub maker: SystemAccount<'info>,
+    /// CHECK: PDA the maker approves as delegate on `maker_ata_a`; only signs.
+    #[account(seeds = [b"order_authority", maker.key().as_ref()], bump)]
+    pub order_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = OrderNonce::INIT_SPACE + OrderNonce::DISCRIMINATOR.len(),
+        seeds = [b"order_nonce", maker.key().as_ref(), order.nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub order_nonce: Box<Account<'info, OrderNonce>>,
+
+    #[account(address = order.mint_a @ EscrowError::InvalidMintA)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = order.mint_b @ EscrowError::InvalidMintB)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: address is checked against the instructions sysvar id.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    /// A fill makes and takes the order at once, so either pause stops it.
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !Config::load(&config)?.make_paused @ EscrowError::MakePaused,
+        constraint = !Config::load(&config)?.take_paused @ EscrowError::TakePaused,
+    )]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds = [b"mint_listing", mint_a.key().as_ref()],
+        bump,
+        constraint = Config::load(&config)?.allows_mint(&mint_a_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_a_listing: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds = [b"mint_listing", mint_b.key().as_ref()],
+        bump,
+        constraint = Config::load(&config)?.allows_mint(&mint_b_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_b_listing: UncheckedAccount<'info>,
+}
+
+impl<'info> FillSignedOrder<'info> {
+    /// The Ed25519 precompile must run immediately before this instruction and
+    /// verify exactly one signature by `maker` over `order.message()`, with the
+    /// key, signature and message all stored in its own instruction data.
+    fn verify_signature(&self, order: &SignedOrder) -> Result<()> {
+        let ix_sysvar = self.instructions.to_account_info();
+        let current = load_current_index_checked(&ix_sysvar)? as usize;
+        require_gt!(current, 0, EscrowError::InvalidSignature);
+        let ix = load_instruction_at_checked(current - 1, &ix_sysvar)?;
+        require_keys_eq!(ix.program_id, ed25519_program::ID, EscrowError::InvalidSignature);
+        require!(ix.accounts.is_empty(), EscrowError::InvalidSignature);
+
+        let data = ix.data.as_slice();
+        // [num_signatures: u8, padding: u8, offsets: 7 x u16]
+        require!(data.len() >= 16 && data[0] == 1, EscrowError::InvalidSignature);
+        let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+        let public_key_offset = read_u16(6) as usize;
+        let message_offset = read_u16(10) as usize;
+        let message_size = read_u16(12) as usize;
+        require!(
+            [read_u16(4), read_u16(8), read_u16(14)]
+                .iter()
+                .all(|&index| index == u16::MAX),
+            EscrowError::InvalidSignature
+        );
+
+        let public_key = data
+            .get(public_key_offset..public_key_offset + 32)
+            .ok_or(EscrowError::InvalidSignature)?;
+        let message = data
+            .get(message_offset..message_offset + message_size)
+            .ok_or(EscrowError::InvalidSignature)?;
+        require!(
+            public_key == order.maker.as_ref() && message == order.message()?.as_slice(),
+            EscrowError::InvalidSignature
+        );
+        Ok(())
+    }
+    fn consume_nonce(&mut self, order: &SignedOrder, bump: u8) -> Result<()> {
+        require!(!self.order_nonce.used, EscrowError::OrderAlreadyFilled);
+        require!(
+            Clock::get()?.unix_timestamp <= order.expiry,
+            EscrowError::OrderExpired
+        );
+        self.order_nonce.set_inner(OrderNonce {
+            maker: order.maker,
+            nonce: order.nonce,
+            used: true,
+            bump,
+        });
+        Ok(())
+    }
+    fn check_allowance(&self, order: &SignedOrder) -> Result<()> {
+        require!(
+            self.maker_ata_a.delegate == COption::Some(self.order_authority.key())
+                && self.maker_ata_a.delegated_amount >= order.amount,
+            EscrowError::InsufficientAllowance
+        );
+        require_gte!(
+            self.maker_ata_a.amount,
+            order.amount,
+            EscrowError::InsufficientBalance
+        );
+        Ok(())
+    }
+    fn transfer_to_maker(&mut self, order: &SignedOrder) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.taker_ata_b.to_account_info(),
+                    to: self.maker_ata_b.to_account_info(),
+                    mint: self.mint_b.to_account_info(),
+                    authority: self.taker.to_account_info(),
+                },
+            ),
+            order.receive,
+            self.mint_b.decimals,
+        )?;
+        Ok(())
+    }
+    fn withdraw_from_maker(&mut self, order: &SignedOrder, bump: u8) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"order_authority",
+            self.maker.to_account_info().key.as_ref(),
+            &[bump],
+        ]];
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    to: self.taker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.order_authority.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            order.amount,
+            self.mint_a.decimals,
+        )?;
+        Ok(())
+    }
+}
+
+/// `accepted_hazards` works as in `make`, for the mints of the order.
+pub fn handler(
+    ctx: Context<FillSignedOrder>,
+    order: SignedOrder,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(order.receive, 0, EscrowError::InvalidAmount);
+    require_gt!(order.amount, 0, EscrowError::InvalidAmount);
+    check_not_nft(&ctx.accounts.mint_a)?;
+    check_mint_hazards(&ctx.accounts.mint_a.to_account_info(), accepted_hazards)?;
+    check_mint_hazards(&ctx.accounts.mint_b.to_account_info(), accepted_hazards)?;
+
+    ctx.accounts.verify_signature(&order)?;
+    ctx.accounts.consume_nonce(&order, ctx.bumps.order_nonce)?;
+    ctx.accounts.check_allowance(&order)?;
+    ctx.accounts.transfer_to_maker(&order)?;
+    ctx.accounts
+        .withdraw_from_maker(&order, ctx.bumps.order_authority)?;
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::errors::EscrowError;
+use crate::program::AnchorEscrow;
+use crate::state::Config;
+use anchor_lang::prelude::*;
+
+/// Creates the program config with the signer as admin. Only the program's
+/// upgrade authority may sign, so watching the deploy and racing to this
+/// instruction gains nothing.
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Config::INIT_SPACE + Config::DISCRIMINATOR.len(),
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, AnchorEscrow>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ EscrowError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeConfig>) -> Result<()> {
+    ctx.accounts.config.set_inner(Config {
+        admin: ctx.accounts.admin.key(),
+        pending_admin: None,
+        make_paused: false,
+        take_paused: false,
+        allowlist_only: false,
+        max_referral_bps: 0,
+        bump: ctx.bumps.config,
+    });
+    Ok(())
+}
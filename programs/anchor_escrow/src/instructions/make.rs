@@ -1,5 +1,10 @@
 use crate::errors::EscrowError;
-use crate::state::Escrow;
+use crate::extension::{set_extensions, EscrowExtension};
+use crate::nft::check_not_nft;
+use crate::oracle::OracleConfig;
+use crate::price::Price;
+use crate::state::{Config, Escrow, EscrowKind, EscrowStatus, MakerState, Registry};
+use crate::token_ext::check_mint_hazards;
 use anchor_lang::prelude::*;
 
 use anchor_spl::{
@@ -48,21 +53,114 @@ pub struct Make<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+    #[account(
+        init_if_needed,
+        payer=maker,
+        space=MakerState::INIT_SPACE + MakerState::DISCRIMINATOR.len(),
+        seeds=[b"maker_state",maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        init_if_needed,
+        payer=maker,
+        space=Registry::space(Registry::GROWTH),
+        seeds=[b"pair_registry",mint_a.key().as_ref(),mint_b.key().as_ref()],
+        bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        init_if_needed,
+        payer=maker,
+        space=Registry::space(Registry::GROWTH),
+        seeds=[b"maker_registry",maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    #[account(
+        seeds=[b"config"],
+        bump,
+        constraint=!Config::load(&config)?.make_paused @ EscrowError::MakePaused,
+    )]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds=[b"mint_listing",mint_a.key().as_ref()],
+        bump,
+        constraint=Config::load(&config)?.allows_mint(&mint_a_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_a_listing: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds=[b"mint_listing",mint_b.key().as_ref()],
+        bump,
+        constraint=Config::load(&config)?.allows_mint(&mint_b_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_b_listing: UncheckedAccount<'info>,
 }
 
 impl<'info> Make<'info> {
-    fn populate_escrow(&mut self, seed: u64, amount: u64, bump: u8) -> Result<()> {
+    pub(crate) fn populate_escrow(
+        &mut self,
+        seed: u64,
+        receive: u64,
+        deposited: u64,
+        price: Price,
+        oracle: Option<OracleConfig>,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        self.maker_state.init_if_new(self.maker.key(), bumps.maker_state);
+        self.maker_state.open_escrow()?;
+        self.maker_state.claim_seed(seed);
+        let now = Clock::get()?.unix_timestamp;
         self.escrow.set_inner(Escrow {
+            version: Escrow::VERSION,
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
-            receive: amount,
-            bump,
+            receive,
+            bump: bumps.escrow,
+            kind: EscrowKind::Vault,
+            deposited,
+            filled: 0,
+            oracle,
+            price,
+            status: EscrowStatus::Open,
+            fill_count: 0,
+            created_at: now,
+            updated_at: now,
         });
-        Ok(())
+        self.register(bumps)
+    }
+    /// Lists the new escrow in its mint pair's and its maker's registry.
+    fn register(&mut self, bumps: &MakeBumps) -> Result<()> {
+        let escrow = self.escrow.key();
+        let maker = self.maker.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        self.pair_registry.init_if_new(bumps.pair_registry);
+        Registry::insert(&mut self.pair_registry, &maker, &system_program, escrow)?;
+        self.maker_registry.init_if_new(bumps.maker_registry);
+        Registry::insert(&mut self.maker_registry, &maker, &system_program, escrow)
     }
-    fn deposit_tokens(&mut self, amount: u64) -> Result<()> {
+    /// Refuses mints with hazards the maker has not accepted; see
+    /// `token_ext::hazard`.
+    pub(crate) fn check_mint_hazards(&self, accepted_hazards: u8) -> Result<()> {
+        check_mint_hazards(&self.mint_a.to_account_info(), accepted_hazards)?;
+        check_mint_hazards(&self.mint_b.to_account_info(), accepted_hazards)
+    }
+    pub(crate) fn set_extensions(&mut self, extensions: &[EscrowExtension]) -> Result<()> {
+        set_extensions(
+            &self.escrow.to_account_info(),
+            &self.maker.to_account_info(),
+            &self.system_program.to_account_info(),
+            extensions,
+        )
+    }
+    /// Returns what actually arrived in the vault, which is less than
+    /// `amount` for mints with a Token-2022 transfer fee.
+    pub(crate) fn deposit_tokens(&mut self, amount: u64) -> Result<u64> {
         transfer_checked(
             CpiContext::new(
                 self.token_program.to_account_info(),
@@ -76,16 +174,30 @@ impl<'info> Make<'info> {
             amount,
             self.mint_a.decimals,
         )?;
-        Ok(())
+        self.vault.reload()?;
+        Ok(self.vault.amount)
     }
 }
 
-pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    receive: u64,
+    amount: u64,
+    extensions: Vec<EscrowExtension>,
+    accepted_hazards: u8,
+) -> Result<()> {
     require_gt!(receive, 0, EscrowError::InvalidAmount);
     require_gt!(amount, 0, EscrowError::InvalidAmount);
+    check_not_nft(&ctx.accounts.mint_a)?;
+    ctx.accounts.check_mint_hazards(accepted_hazards)?;
 
+    let deposited = ctx.accounts.deposit_tokens(amount)?;
+    let price = Price::from_amounts(receive, deposited)?;
     ctx.accounts
-        .populate_escrow(seed, receive, ctx.bumps.escrow)?;
-    ctx.accounts.deposit_tokens(amount)?;
+        .populate_escrow(seed, receive, deposited, price, None, &ctx.bumps)?;
+    if !extensions.is_empty() {
+        ctx.accounts.set_extensions(&extensions)?;
+    }
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,38 @@
+use crate::errors::EscrowError;
+use crate::extension::{set_extension, CollectionOffer};
+use crate::instructions::Make;
+use crate::nft::check_not_nft;
+use crate::price::Price;
+use anchor_lang::prelude::*;
+
+/// Bids `price` of mint_a apiece for `quantity` NFTs from the collection
+/// passed as mint_b: a Token-2022 group mint, or with the `metaplex` feature
+/// a collection NFT's mint. Holders sell into it one NFT at a time with
+/// `sell_into_bid`.
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    price: u64,
+    quantity: u64,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(price, 0, EscrowError::InvalidAmount);
+    require_gt!(quantity, 0, EscrowError::InvalidAmount);
+    check_not_nft(&ctx.accounts.mint_a)?;
+    ctx.accounts.check_mint_hazards(accepted_hazards)?;
+
+    let amount = price
+        .checked_mul(quantity)
+        .ok_or(EscrowError::MathOverflow)?;
+    let deposited = ctx.accounts.deposit_tokens(amount)?;
+    require_gte!(deposited, quantity, EscrowError::InvalidAmount);
+    let price = Price::from_amounts(quantity, deposited)?;
+    ctx.accounts
+        .populate_escrow(seed, quantity, deposited, price, None, &ctx.bumps)?;
+    set_extension(
+        &ctx.accounts.escrow.to_account_info(),
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &CollectionOffer {},
+    )
+}
@@ -0,0 +1,14 @@
+use crate::instructions::Make;
+use anchor_lang::prelude::*;
+
+/// Offers `amount` of mint_a for any one NFT from the collection passed as
+/// mint_b: a collection bid for a single item. The taker picks which member
+/// to deliver; `take` checks its membership and takes the whole offer.
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    amount: u64,
+    accepted_hazards: u8,
+) -> Result<()> {
+    crate::instructions::make_collection_bid::handler(ctx, seed, amount, 1, accepted_hazards)
+}
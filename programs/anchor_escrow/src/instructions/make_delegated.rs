@@ -0,0 +1,175 @@
+use crate::errors::EscrowError;
+use crate::nft::check_not_nft;
+use crate::price::Price;
+use crate::state::{Config, Escrow, EscrowKind, EscrowStatus, MakerState, Registry};
+use crate::token_ext::check_mint_hazards;
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{
+    approve_checked, ApproveChecked, Mint, TokenAccount, TokenInterface,
+};
+
+/// Non-custodial variant of `Make`: no vault is created, the maker instead
+/// approves the escrow PDA as delegate of `maker_ata_a` for `amount`.
+///
+/// A token account holds a single delegate, so only one delegated order per
+/// `maker_ata_a` can be live at a time.
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeDelegated<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = maker,
+        space = Escrow::INIT_SPACE + Escrow::DISCRIMINATOR.len(),
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mint::token_program = token_program)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerState::INIT_SPACE + MakerState::DISCRIMINATOR.len(),
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = Registry::space(Registry::GROWTH),
+        seeds = [b"pair_registry", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = Registry::space(Registry::GROWTH),
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !Config::load(&config)?.make_paused @ EscrowError::MakePaused,
+    )]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds = [b"mint_listing", mint_a.key().as_ref()],
+        bump,
+        constraint = Config::load(&config)?.allows_mint(&mint_a_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_a_listing: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds = [b"mint_listing", mint_b.key().as_ref()],
+        bump,
+        constraint = Config::load(&config)?.allows_mint(&mint_b_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_b_listing: UncheckedAccount<'info>,
+}
+
+impl<'info> MakeDelegated<'info> {
+    fn populate_escrow(
+        &mut self,
+        seed: u64,
+        receive: u64,
+        amount: u64,
+        price: Price,
+        bumps: &MakeDelegatedBumps,
+    ) -> Result<()> {
+        self.maker_state.init_if_new(self.maker.key(), bumps.maker_state);
+        self.maker_state.open_escrow()?;
+        self.maker_state.claim_seed(seed);
+        let now = Clock::get()?.unix_timestamp;
+        self.escrow.set_inner(Escrow {
+            version: Escrow::VERSION,
+            seed,
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            receive,
+            bump: bumps.escrow,
+            kind: EscrowKind::Delegated,
+            deposited: amount,
+            filled: 0,
+            oracle: None,
+            price,
+            status: EscrowStatus::Open,
+            fill_count: 0,
+            created_at: now,
+            updated_at: now,
+        });
+        self.register(bumps)
+    }
+    /// Lists the new escrow in its mint pair's and its maker's registry.
+    fn register(&mut self, bumps: &MakeDelegatedBumps) -> Result<()> {
+        let escrow = self.escrow.key();
+        let maker = self.maker.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        self.pair_registry.init_if_new(bumps.pair_registry);
+        Registry::insert(&mut self.pair_registry, &maker, &system_program, escrow)?;
+        self.maker_registry.init_if_new(bumps.maker_registry);
+        Registry::insert(&mut self.maker_registry, &maker, &system_program, escrow)
+    }
+    fn approve_escrow(&mut self, amount: u64) -> Result<()> {
+        approve_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                ApproveChecked {
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    delegate: self.escrow.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+        Ok(())
+    }
+}
+
+pub fn handler(
+    ctx: Context<MakeDelegated>,
+    seed: u64,
+    receive: u64,
+    amount: u64,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(receive, 0, EscrowError::InvalidAmount);
+    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    check_not_nft(&ctx.accounts.mint_a)?;
+    check_mint_hazards(&ctx.accounts.mint_a.to_account_info(), accepted_hazards)?;
+    check_mint_hazards(&ctx.accounts.mint_b.to_account_info(), accepted_hazards)?;
+    require_gte!(
+        ctx.accounts.maker_ata_a.amount,
+        amount,
+        EscrowError::InsufficientBalance
+    );
+
+    let price = Price::from_amounts(receive, amount)?;
+    ctx.accounts
+        .populate_escrow(seed, receive, amount, price, &ctx.bumps)?;
+    ctx.accounts.approve_escrow(amount)?;
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use crate::errors::EscrowError;
+use crate::extension::EscrowExtension;
+use crate::instructions::{make, Make};
+use anchor_lang::prelude::*;
+
+/// `make` at the maker's counter seed. The escrow address is derived from
+/// `seed`, so the client reads `maker_state.next_seed` (0 before the first
+/// escrow) and passes it; a stale read fails with `SeedMismatch` rather than
+/// opening the escrow somewhere unexpected. Explicit seeds passed to `make`
+/// move the counter past themselves, so the two never collide.
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    receive: u64,
+    amount: u64,
+    extensions: Vec<EscrowExtension>,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_eq!(
+        seed,
+        ctx.accounts.maker_state.next_seed,
+        EscrowError::SeedMismatch
+    );
+    make::handler(ctx, seed, receive, amount, extensions, accepted_hazards)
+}
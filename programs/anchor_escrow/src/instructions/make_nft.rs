@@ -0,0 +1,46 @@
+use crate::errors::EscrowError;
+use crate::extension::set_extension;
+use crate::instructions::Make;
+use crate::nft::{check_collection_member, check_nft, read_royalties};
+use crate::price::Price;
+use anchor_lang::prelude::*;
+
+/// `make` for a single NFT as mint_a. The NFT's royalties are read from its
+/// metadata now and kept in the escrow's `NftRoyalties` extension; each take
+/// pays them to the creators out of the maker's proceeds. With the `metaplex`
+/// feature, pass the NFT's Token Metadata account as the first remaining
+/// account to read royalties from it. With `collection`, the NFT must also be
+/// a verified member of it.
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    receive: u64,
+    collection: Option<Pubkey>,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(receive, 0, EscrowError::InvalidAmount);
+    check_nft(&ctx.accounts.mint_a)?;
+    if let Some(collection) = collection {
+        check_collection_member(
+            &ctx.accounts.mint_a.to_account_info(),
+            &collection,
+            ctx.remaining_accounts,
+        )?;
+    }
+    ctx.accounts.check_mint_hazards(accepted_hazards)?;
+    let royalties = read_royalties(
+        &ctx.accounts.mint_a.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    let deposited = ctx.accounts.deposit_tokens(1)?;
+    let price = Price::from_amounts(receive, deposited)?;
+    ctx.accounts
+        .populate_escrow(seed, receive, deposited, price, None, &ctx.bumps)?;
+    set_extension(
+        &ctx.accounts.escrow.to_account_info(),
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &royalties,
+    )
+}
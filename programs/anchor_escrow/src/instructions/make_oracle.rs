@@ -0,0 +1,37 @@
+use crate::errors::EscrowError;
+use crate::instructions::Make;
+use crate::nft::check_not_nft;
+use crate::oracle::OracleConfig;
+use crate::price::Price;
+use anchor_lang::prelude::*;
+
+/// Same accounts and vault deposit as `make`, but the mint_b amount owed is
+/// read from `oracle.price_feed` when the escrow is taken.
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    amount: u64,
+    oracle: OracleConfig,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    require_gt!(oracle.max_staleness, 0, EscrowError::InvalidOracleStaleness);
+    require_gt!(
+        oracle.max_confidence_bps,
+        0,
+        EscrowError::InvalidOracleConfidence
+    );
+    check_not_nft(&ctx.accounts.mint_a)?;
+    ctx.accounts.check_mint_hazards(accepted_hazards)?;
+
+    let deposited = ctx.accounts.deposit_tokens(amount)?;
+    ctx.accounts.populate_escrow(
+        seed,
+        0,
+        deposited,
+        Price::default(),
+        Some(oracle),
+        &ctx.bumps,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use crate::errors::EscrowError;
+use crate::instructions::Make;
+use crate::nft::check_not_nft;
+use crate::price::{Price, Rounding};
+use anchor_lang::prelude::*;
+
+/// `make` with the ask given as a rate in whole tokens: `price_numerator`
+/// mint_b for every `price_denominator` mint_a. The rate is normalised by
+/// both mints' decimals and `receive` is derived from it for the deposit,
+/// rounded up.
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    amount: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    check_not_nft(&ctx.accounts.mint_a)?;
+    ctx.accounts.check_mint_hazards(accepted_hazards)?;
+
+    let price = Price::from_ui(
+        price_numerator,
+        price_denominator,
+        ctx.accounts.mint_a.decimals,
+        ctx.accounts.mint_b.decimals,
+    )?;
+    let deposited = ctx.accounts.deposit_tokens(amount)?;
+    let receive = price.amount_b_for(deposited, Rounding::Up)?;
+
+    ctx.accounts
+        .populate_escrow(seed, receive, deposited, price, None, &ctx.bumps)?;
+    Ok(())
+}
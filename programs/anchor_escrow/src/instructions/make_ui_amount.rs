@@ -0,0 +1,43 @@
+use crate::errors::EscrowError;
+use crate::extension::{set_extension, UiReceive};
+use crate::instructions::Make;
+use crate::nft::check_not_nft;
+use crate::price::Price;
+use crate::token_ext::{ui_multiplier, ui_to_raw};
+use anchor_lang::prelude::*;
+
+/// `make` with `receive_ui` in mint_b UI units times 10^decimals, the amount
+/// a wallet shows, rather than raw. For interest-bearing or scaled-UI mints
+/// it is converted at the current rate; the entered amount and the time of
+/// conversion are kept in the escrow's `UiReceive` extension.
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    receive_ui: u64,
+    amount: u64,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(receive_ui, 0, EscrowError::InvalidAmount);
+    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    check_not_nft(&ctx.accounts.mint_a)?;
+    ctx.accounts.check_mint_hazards(accepted_hazards)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let multiplier = ui_multiplier(&ctx.accounts.mint_b.to_account_info(), now)?;
+    let receive = ui_to_raw(receive_ui, multiplier)?;
+    require_gt!(receive, 0, EscrowError::InvalidAmount);
+
+    let deposited = ctx.accounts.deposit_tokens(amount)?;
+    let price = Price::from_amounts(receive, deposited)?;
+    ctx.accounts
+        .populate_escrow(seed, receive, deposited, price, None, &ctx.bumps)?;
+    set_extension(
+        &ctx.accounts.escrow.to_account_info(),
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &UiReceive {
+            receive_ui,
+            converted_at: now,
+        },
+    )
+}
@@ -0,0 +1,155 @@
+use crate::errors::EscrowError;
+use crate::nft::check_not_nft;
+use crate::state::{Config, EscrowZeroCopy, MakerState, Registry};
+use crate::token_ext::check_mint_hazards;
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeZeroCopy<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = maker,
+        space = EscrowZeroCopy::LEN,
+        seeds = [b"escrow_zc", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountLoader<'info, EscrowZeroCopy>,
+    #[account(mint::token_program = token_program)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mint::token_program = token_program)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerState::INIT_SPACE + MakerState::DISCRIMINATOR.len(),
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = Registry::space(Registry::GROWTH),
+        seeds = [b"pair_registry", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = Registry::space(Registry::GROWTH),
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !Config::load(&config)?.make_paused @ EscrowError::MakePaused,
+    )]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds = [b"mint_listing", mint_a.key().as_ref()],
+        bump,
+        constraint = Config::load(&config)?.allows_mint(&mint_a_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_a_listing: UncheckedAccount<'info>,
+    /// CHECK: may not exist; read through `Config::allows_mint`.
+    #[account(
+        seeds = [b"mint_listing", mint_b.key().as_ref()],
+        bump,
+        constraint = Config::load(&config)?.allows_mint(&mint_b_listing)? @ EscrowError::MintNotAllowed,
+    )]
+    pub mint_b_listing: UncheckedAccount<'info>,
+}
+
+impl<'info> MakeZeroCopy<'info> {
+    /// Lists the new escrow in its mint pair's and its maker's registry.
+    fn register(&mut self, bumps: &MakeZeroCopyBumps) -> Result<()> {
+        let escrow = self.escrow.key();
+        let maker = self.maker.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        self.pair_registry.init_if_new(bumps.pair_registry);
+        Registry::insert(&mut self.pair_registry, &maker, &system_program, escrow)?;
+        self.maker_registry.init_if_new(bumps.maker_registry);
+        Registry::insert(&mut self.maker_registry, &maker, &system_program, escrow)
+    }
+    fn deposit_tokens(&mut self, amount: u64) -> Result<u64> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+        self.vault.reload()?;
+        Ok(self.vault.amount)
+    }
+}
+
+pub fn handler(
+    ctx: Context<MakeZeroCopy>,
+    seed: u64,
+    receive: u64,
+    amount: u64,
+    accepted_hazards: u8,
+) -> Result<()> {
+    require_gt!(receive, 0, EscrowError::InvalidAmount);
+    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    check_not_nft(&ctx.accounts.mint_a)?;
+    check_mint_hazards(&ctx.accounts.mint_a.to_account_info(), accepted_hazards)?;
+    check_mint_hazards(&ctx.accounts.mint_b.to_account_info(), accepted_hazards)?;
+    // `escrow_zc` seeds are their own address space, so no seed is claimed
+    let maker = ctx.accounts.maker.key();
+    ctx.accounts.maker_state.init_if_new(maker, ctx.bumps.maker_state);
+    ctx.accounts.maker_state.open_escrow()?;
+
+    let deposited = ctx.accounts.deposit_tokens(amount)?;
+
+    let mut escrow = ctx.accounts.escrow.load_init()?;
+    escrow.seed = seed;
+    escrow.receive = receive;
+    escrow.deposited = deposited;
+    escrow.created_at = Clock::get()?.unix_timestamp;
+    escrow.maker = ctx.accounts.maker.key();
+    escrow.mint_a = ctx.accounts.mint_a.key();
+    escrow.mint_b = ctx.accounts.mint_b.key();
+    escrow.bump = ctx.bumps.escrow;
+    escrow.version = EscrowZeroCopy::VERSION;
+    drop(escrow);
+    ctx.accounts.register(&ctx.bumps)
+}
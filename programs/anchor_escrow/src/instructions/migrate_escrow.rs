@@ -0,0 +1,175 @@
+use crate::{
+    errors::EscrowError,
+    price::Price,
+    state::{realloc_account, Escrow, EscrowKind, EscrowStatus, MakerState, Registry},
+};
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// `Escrow` as the original release wrote it: a plain vault escrow with no
+/// version byte, fill tracking or price.
+#[derive(AnchorDeserialize, InitSpace)]
+struct EscrowV0 {
+    seed: u64,
+    maker: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    receive: u64,
+    bump: u8,
+}
+
+impl EscrowV0 {
+    /// Nothing was ever taken from a v0 escrow (takes closed it), so the
+    /// vault balance is its deposit. The creation time was not recorded and
+    /// is taken to be the migration.
+    fn upgrade(self, deposited: u64, now: i64) -> Result<Escrow> {
+        Ok(Escrow {
+            version: Escrow::VERSION,
+            seed: self.seed,
+            maker: self.maker,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            receive: self.receive,
+            bump: self.bump,
+            kind: EscrowKind::Vault,
+            deposited,
+            filled: 0,
+            oracle: None,
+            price: Price::from_amounts(self.receive, deposited)?,
+            status: EscrowStatus::Open,
+            fill_count: 0,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+/// Rewrites an escrow created by the original release into the current
+/// layout, growing the account and charging the rent difference to the maker.
+/// The escrow is also counted in `MakerState` and listed in the registries,
+/// both of which postdate it.
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: may still be in an old layout that `Account<Escrow>` cannot
+    /// deserialize; the discriminator, maker and seeds are checked by hand.
+    #[account(mut, owner = crate::ID)]
+    pub escrow: UncheckedAccount<'info>,
+
+    /// Must match the escrow's `mint_a`, checked in `load_old`
+    #[account(mint::token_program = token_program)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: must match the escrow's `mint_b`, checked in `load_old`
+    pub mint_b: UncheckedAccount<'info>,
+
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = MakerState::INIT_SPACE + MakerState::DISCRIMINATOR.len(),
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = Registry::space(Registry::GROWTH),
+        seeds = [b"pair_registry", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = Registry::space(Registry::GROWTH),
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+}
+
+impl<'info> MigrateEscrow<'info> {
+    fn load_old(&self) -> Result<EscrowV0> {
+        let data = self.escrow.try_borrow_data()?;
+        require!(
+            data.starts_with(Escrow::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+
+        let fields = &data[Escrow::DISCRIMINATOR.len()..];
+        if data.len() >= Escrow::LEN && fields[0] == Escrow::VERSION {
+            return err!(EscrowError::EscrowAlreadyMigrated);
+        }
+        require_eq!(
+            data.len(),
+            Escrow::DISCRIMINATOR.len() + EscrowV0::INIT_SPACE,
+            EscrowError::UnsupportedEscrowVersion
+        );
+        let old = EscrowV0::deserialize(&mut &fields[..])?;
+
+        require_keys_eq!(old.maker, self.maker.key(), EscrowError::InvalidMaker);
+        require_keys_eq!(old.mint_a, self.mint_a.key(), EscrowError::InvalidMintA);
+        require_keys_eq!(old.mint_b, self.mint_b.key(), EscrowError::InvalidMintB);
+        let expected = Pubkey::create_program_address(
+            &[
+                b"escrow",
+                old.maker.as_ref(),
+                &old.seed.to_le_bytes(),
+                &[old.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(expected, self.escrow.key(), ErrorCode::ConstraintSeeds);
+
+        Ok(old)
+    }
+    fn register(&mut self, bumps: &MigrateEscrowBumps) -> Result<()> {
+        self.maker_state.init_if_new(self.maker.key(), bumps.maker_state);
+        self.maker_state.open_escrows = self.maker_state.open_escrows.saturating_add(1);
+
+        let escrow = self.escrow.key();
+        let maker = self.maker.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        self.pair_registry.init_if_new(bumps.pair_registry);
+        Registry::insert(&mut self.pair_registry, &maker, &system_program, escrow)?;
+        self.maker_registry.init_if_new(bumps.maker_registry);
+        Registry::insert(&mut self.maker_registry, &maker, &system_program, escrow)
+    }
+}
+
+pub fn handler(ctx: Context<MigrateEscrow>) -> Result<()> {
+    let deposited = ctx.accounts.vault.amount;
+    require_gt!(deposited, 0, EscrowError::InvalidAmount);
+    let escrow = ctx
+        .accounts
+        .load_old()?
+        .upgrade(deposited, Clock::get()?.unix_timestamp)?;
+
+    realloc_account(
+        &ctx.accounts.escrow,
+        &ctx.accounts.maker,
+        &ctx.accounts.system_program,
+        Escrow::LEN,
+    )?;
+    escrow.try_serialize(&mut &mut ctx.accounts.escrow.try_borrow_mut_data()?[..])?;
+
+    ctx.accounts.register(&ctx.bumps)?;
+
+    Ok(())
+}
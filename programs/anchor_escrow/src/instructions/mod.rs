@@ -5,4 +5,65 @@ pub mod refund;
 pub use refund::*;
 
 pub mod take;
-pub use take::*;
\ No newline at end of file
+pub use take::*;
+
+pub mod make_next;
+
+pub mod make_oracle;
+
+pub mod make_priced;
+
+pub mod make_ui_amount;
+pub mod make_nft;
+pub mod make_collection_offer;
+pub mod make_collection_bid;
+
+pub mod take_partial;
+pub mod sell_into_bid;
+
+pub mod make_delegated;
+pub use make_delegated::*;
+
+pub mod take_delegated;
+pub use take_delegated::*;
+
+pub mod cancel_delegated;
+pub use cancel_delegated::*;
+
+pub mod fill_signed_order;
+pub use fill_signed_order::*;
+
+pub mod quote;
+pub use quote::*;
+
+pub mod sweep_excess;
+pub use sweep_excess::*;
+
+pub mod migrate_escrow;
+pub use migrate_escrow::*;
+
+pub mod add_extensions;
+pub use add_extensions::*;
+
+pub mod make_zero_copy;
+pub use make_zero_copy::*;
+pub mod take_zero_copy;
+pub use take_zero_copy::*;
+pub mod refund_zero_copy;
+pub use refund_zero_copy::*;
+pub mod initialize_config;
+pub use initialize_config::*;
+pub mod set_pause;
+pub use set_pause::*;
+pub mod propose_admin;
+pub use propose_admin::*;
+pub mod accept_admin;
+pub use accept_admin::*;
+pub mod set_allowlist_only;
+pub use set_allowlist_only::*;
+pub mod set_mint_listing;
+pub use set_mint_listing::*;
+pub mod remove_mint_listing;
+pub use remove_mint_listing::*;
+pub mod set_referral_cap;
+pub use set_referral_cap::*;
@@ -0,0 +1,22 @@
+use crate::{errors::EscrowError, state::Config};
+use anchor_lang::prelude::*;
+
+/// First half of an admin rotation: records `new_admin`, which must then
+/// sign `accept_admin`. Proposing again replaces the pending admin.
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handler(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.config.pending_admin = Some(new_admin);
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use crate::errors::EscrowError;
+use crate::extension::{get_extension, NftRoyalties};
+use crate::price::Rounding;
+use crate::state::{Config, Escrow, EscrowKind};
+use crate::token_ext::{raw_to_ui, transfer_fee, ui_multiplier};
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Outcome of a `take`/`take_partial` of `amount_a`, as the program would
+/// compute it now.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TakeQuote {
+    /// mint_a leaving the vault.
+    pub amount_a: u64,
+    /// Token-2022 transfer fee withheld from `amount_a`.
+    pub transfer_fee_a: u64,
+    /// mint_a arriving in the taker's account.
+    pub amount_a_received: u64,
+    /// mint_b the maker is owed for `amount_a`.
+    pub amount_b: u64,
+    /// NFT creator royalties paid out of `amount_b`.
+    pub royalty_b: u64,
+    /// Referral fee charged to the taker on top of `amount_b`.
+    pub fee_b: u64,
+    /// Token-2022 transfer fee withheld from the maker's payment.
+    pub transfer_fee_b: u64,
+    /// mint_b debited from the taker.
+    pub amount_b_paid: u64,
+    /// mint_b arriving in the maker's account.
+    pub amount_b_received: u64,
+    /// `amount_a_received` as a wallet shows it now: UI units times
+    /// 10^decimals, which differs from raw for interest-bearing and
+    /// scaled-UI mints.
+    pub amount_a_received_ui: u64,
+    /// `amount_b_paid` as a wallet shows it now.
+    pub amount_b_paid_ui: u64,
+}
+
+/// Read-only mirror of `Take`; nothing is written or signed.
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    #[account(
+        seeds = [b"escrow", escrow.maker.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = mint_b @ EscrowError::InvalidMintB,
+        constraint = escrow.kind == EscrowKind::Vault @ EscrowError::InvalidEscrowKind,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mint::token_program = token_program)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: required for oracle-priced escrows, validated by `PriceFeed::load`
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+impl<'info> Quote<'info> {
+    fn quote(&self, amount_a: u64, referral_bps: u16) -> Result<TakeQuote> {
+        let price = self.escrow.current_price(
+            self.price_feed.as_deref(),
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+        )?;
+        let amount_b = price.amount_b_for(amount_a, Rounding::Up)?;
+        let fee_b = Config::load(&self.config)?.referral_fee(amount_b, referral_bps)?;
+        let amount_b_paid = amount_b
+            .checked_add(fee_b)
+            .ok_or(EscrowError::MathOverflow)?;
+
+        let royalty_b = get_extension::<NftRoyalties>(&self.escrow.to_account_info())?
+            .map_or(0, |royalties| royalties.royalty(amount_b));
+        let maker_payment = amount_b - royalty_b;
+
+        let transfer_fee_a = transfer_fee(&self.mint_a.to_account_info(), amount_a)?;
+        let transfer_fee_b = transfer_fee(&self.mint_b.to_account_info(), maker_payment)?;
+        let amount_a_received = amount_a - transfer_fee_a;
+
+        let now = Clock::get()?.unix_timestamp;
+        let multiplier_a = ui_multiplier(&self.mint_a.to_account_info(), now)?;
+        let multiplier_b = ui_multiplier(&self.mint_b.to_account_info(), now)?;
+
+        Ok(TakeQuote {
+            amount_a,
+            transfer_fee_a,
+            amount_a_received,
+            amount_b,
+            royalty_b,
+            fee_b,
+            transfer_fee_b,
+            amount_b_paid,
+            amount_b_received: maker_payment - transfer_fee_b,
+            amount_a_received_ui: raw_to_ui(amount_a_received, multiplier_a)?,
+            amount_b_paid_ui: raw_to_ui(amount_b_paid, multiplier_b)?,
+        })
+    }
+}
+
+/// Quotes a take of `amount` mint_a, or of everything remaining when `None`,
+/// paying a referrer `referral_bps`. The result is returned through
+/// `set_return_data`.
+pub fn handler(ctx: Context<Quote>, amount: Option<u64>, referral_bps: u16) -> Result<TakeQuote> {
+    let remaining = ctx.accounts.escrow.remaining();
+    let amount = amount.unwrap_or(remaining);
+    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    require_gte!(remaining, amount, EscrowError::InvalidAmount);
+
+    ctx.accounts.quote(amount, referral_bps)
+}
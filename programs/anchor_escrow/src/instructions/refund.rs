@@ -1,4 +1,7 @@
-use crate::{errors::EscrowError, state::Escrow};
+use crate::{
+    errors::EscrowError,
+    state::{Escrow, EscrowKind, EscrowStatus, MakerState, Registry},
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -20,6 +23,7 @@ pub struct Refund<'info> {
         bump = escrow.bump,
         has_one = maker @ EscrowError::InvalidMaker,
         has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = escrow.kind == EscrowKind::Vault @ EscrowError::InvalidEscrowKind,
     )]
     pub escrow: Box<Account<'info, Escrow>>,
 
@@ -48,9 +52,32 @@ pub struct Refund<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+
+    #[account(
+        mut,
+        seeds = [b"pair_registry", mint_a.key().as_ref(), escrow.mint_b.as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+
+    #[account(
+        mut,
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump = maker_registry.bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
 }
 
 impl<'info> Refund<'info> {
+    /// Returns everything in the vault: the unfilled part of `deposited` plus
+    /// any donated excess, all of which belongs to the maker.
     fn withdraw_and_close_vault(&mut self) -> Result<()> {
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
@@ -89,6 +116,13 @@ impl<'info> Refund<'info> {
 }
 
 pub fn handler(ctx: Context<Refund>) -> Result<()> {
+    ctx.accounts
+        .escrow
+        .transition(EscrowStatus::Cancelled, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.maker_state.close_escrow();
+    let escrow = ctx.accounts.escrow.key();
+    ctx.accounts.pair_registry.remove(&escrow);
+    ctx.accounts.maker_registry.remove(&escrow);
     ctx.accounts.withdraw_and_close_vault()?;
 
     Ok(())
@@ -0,0 +1,124 @@
+use crate::{
+    errors::EscrowError,
+    state::{EscrowZeroCopy, MakerState, Registry},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct RefundZeroCopy<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"escrow_zc", maker.key().as_ref(), escrow.load()?.seed.to_le_bytes().as_ref()],
+        bump = escrow.load()?.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+    )]
+    pub escrow: AccountLoader<'info, EscrowZeroCopy>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        mut,
+        seeds = [b"pair_registry", mint_a.key().as_ref(), escrow.load()?.mint_b.as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        mut,
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump = maker_registry.bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+}
+
+impl<'info> RefundZeroCopy<'info> {
+    fn withdraw_and_close_vault(&mut self) -> Result<()> {
+        let (seed, bump) = {
+            let escrow = self.escrow.load()?;
+            (escrow.seed, escrow.bump)
+        };
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow_zc",
+            self.maker.to_account_info().key.as_ref(),
+            &seed.to_le_bytes()[..],
+            &[bump],
+        ]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            self.vault.amount,
+            self.mint_a.decimals,
+        )?;
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination: self.maker.to_account_info(),
+            },
+            &signer_seeds,
+        ))?;
+
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<RefundZeroCopy>) -> Result<()> {
+    ctx.accounts.withdraw_and_close_vault()?;
+    ctx.accounts.maker_state.close_escrow();
+    let escrow = ctx.accounts.escrow.key();
+    ctx.accounts.pair_registry.remove(&escrow);
+    ctx.accounts.maker_registry.remove(&escrow);
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::{
+    errors::EscrowError,
+    state::{Config, MintListing},
+};
+use anchor_lang::prelude::*;
+
+/// Drops a mint's listing and returns its rent to the admin. The mint then
+/// falls back to the default policy.
+#[derive(Accounts)]
+pub struct RemoveMintListing<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"mint_listing", mint_listing.mint.as_ref()],
+        bump = mint_listing.bump,
+    )]
+    pub mint_listing: Account<'info, MintListing>,
+}
+
+pub fn handler(_ctx: Context<RemoveMintListing>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use crate::errors::EscrowError;
+use crate::extension::{get_extension, CollectionOffer};
+use crate::instructions::Take;
+use anchor_lang::prelude::*;
+
+/// Delivers one NFT from the bid's collection, passed as mint_b, for an even
+/// share of what the bid has left; see `Escrow::collection_item_amount`.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Take<'info>>) -> Result<()> {
+    require!(
+        get_extension::<CollectionOffer>(&ctx.accounts.escrow.to_account_info())?.is_some(),
+        EscrowError::NotACollectionOffer
+    );
+    let amount = ctx.accounts.escrow.collection_item_amount()?;
+    ctx.accounts.fill(amount, 0, None, ctx.remaining_accounts)
+}
@@ -0,0 +1,22 @@
+use crate::{errors::EscrowError, state::Config};
+use anchor_lang::prelude::*;
+
+/// Switches between accepting any mint that is not denied and accepting
+/// only mints listed as allowed.
+#[derive(Accounts)]
+pub struct SetAllowlistOnly<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handler(ctx: Context<SetAllowlistOnly>, allowlist_only: bool) -> Result<()> {
+    ctx.accounts.config.allowlist_only = allowlist_only;
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::{
+    errors::EscrowError,
+    state::{Config, MintListing, MintStatus},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// Allows or denies `mint` for new escrows, creating its listing if needed.
+#[derive(Accounts)]
+pub struct SetMintListing<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = MintListing::INIT_SPACE + MintListing::DISCRIMINATOR.len(),
+        seeds = [b"mint_listing", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_listing: Account<'info, MintListing>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetMintListing>, status: MintStatus) -> Result<()> {
+    ctx.accounts.mint_listing.set_inner(MintListing {
+        mint: ctx.accounts.mint.key(),
+        status,
+        bump: ctx.bumps.mint_listing,
+    });
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use crate::{errors::EscrowError, state::Config};
+use anchor_lang::prelude::*;
+
+/// Pauses or resumes making and taking. Refunds are never paused, so makers
+/// can always withdraw.
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handler(ctx: Context<SetPause>, make_paused: bool, take_paused: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.make_paused = make_paused;
+    config.take_paused = take_paused;
+    Ok(())
+}
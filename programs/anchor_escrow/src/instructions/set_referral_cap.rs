@@ -0,0 +1,23 @@
+use crate::{errors::EscrowError, state::Config};
+use anchor_lang::prelude::*;
+
+/// Sets the highest referral fee a take may pay, in bps of the maker's
+/// payment. 0 disables referral fees.
+#[derive(Accounts)]
+pub struct SetReferralCap<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handler(ctx: Context<SetReferralCap>, max_referral_bps: u16) -> Result<()> {
+    require_gte!(10_000, max_referral_bps, EscrowError::ReferralFeeTooHigh);
+    ctx.accounts.config.max_referral_bps = max_referral_bps;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::{
+    errors::EscrowError,
+    state::{Escrow, EscrowKind, MakerState, Registry},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+/// Moves tokens sent to the vault on top of the escrowed `deposited - filled`
+/// back to the maker, so donations can never change what a taker receives.
+#[derive(Accounts)]
+pub struct SweepExcess<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        constraint = escrow.kind == EscrowKind::Vault @ EscrowError::InvalidEscrowKind,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+
+    #[account(
+        mut,
+        seeds = [b"pair_registry", mint_a.key().as_ref(), escrow.mint_b.as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+
+    #[account(
+        mut,
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump = maker_registry.bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+}
+
+impl<'info> SweepExcess<'info> {
+    /// Closes the vault and escrow as well when the order was already fully
+    /// filled and only the excess was keeping them open.
+    fn sweep(&mut self) -> Result<()> {
+        let excess = self.vault.amount.saturating_sub(self.escrow.remaining());
+        require_gt!(excess, 0, EscrowError::NoExcess);
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            excess,
+            self.mint_a.decimals,
+        )?;
+
+        if self.escrow.remaining() > 0 {
+            return Ok(());
+        }
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination: self.maker.to_account_info(),
+            },
+            &signer_seeds,
+        ))?;
+        self.escrow.close(self.maker.to_account_info())?;
+        self.maker_state.close_escrow();
+        self.pair_registry.remove(&self.escrow.key());
+        self.maker_registry.remove(&self.escrow.key());
+
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<SweepExcess>) -> Result<()> {
+    ctx.accounts.sweep()?;
+
+    Ok(())
+}
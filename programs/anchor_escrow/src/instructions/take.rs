@@ -1,9 +1,16 @@
 use crate::errors::EscrowError;
-use crate::state::Escrow;
+use crate::extension::{
+    check_take, get_extension, AllowlistProof, CollectionOffer, Memo, MerkleAllowlist,
+    NftRoyalties, ProceedsDestination, ProceedsSplit,
+};
+use crate::nft::{check_collection_member, check_nft};
+use crate::price::Rounding;
+use crate::state::{Config, Escrow, EscrowKind, MakerState, Registry, TakerFill};
 use anchor_lang::prelude::*;
 
 use anchor_spl::{
     associated_token::AssociatedToken,
+    memo::{build_memo, BuildMemo, Memo as MemoProgram},
     token_interface::{
         close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
         TransferChecked,
@@ -17,12 +24,14 @@ pub struct Take<'info> {
     pub maker: SystemAccount<'info>,
     #[account(
         mut,
-        close=maker,
         seeds=[b"escrow",maker.key().as_ref(),escrow.seed.to_le_bytes().as_ref()],
         bump=escrow.bump,
         has_one=maker @ EscrowError::InvalidMaker,
         has_one=mint_a @ EscrowError::InvalidMintA,
-        has_one=mint_b @ EscrowError::InvalidMintB,
+        constraint=escrow.mint_b == mint_b.key()
+            || get_extension::<CollectionOffer>(&escrow.to_account_info())?.is_some()
+            @ EscrowError::InvalidMintB,
+        constraint=escrow.kind == EscrowKind::Vault @ EscrowError::InvalidEscrowKind,
     )]
     pub escrow: Box<Account<'info, Escrow>>,
 
@@ -35,6 +44,7 @@ pub struct Take<'info> {
         associated_token::token_program=token_program,
     )]
     pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives mint_a unless `taker_destination_a` is passed.
     #[account(
         init_if_needed,
         payer=taker,
@@ -42,7 +52,7 @@ pub struct Take<'info> {
         associated_token::authority=taker,
         associated_token::token_program=token_program,
     )]
-    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub taker_ata_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
        mut,
        associated_token::mint=mint_b,
@@ -50,6 +60,7 @@ pub struct Take<'info> {
        associated_token::token_program=token_program,
     )]
     pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives mint_b unless the escrow has a `ProceedsDestination`.
     #[account(
         init_if_needed,
         payer=taker,
@@ -57,30 +68,274 @@ pub struct Take<'info> {
         associated_token::authority=maker,
         associated_token::token_program=token_program,
     )]
-    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub maker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+    /// CHECK: required for oracle-priced escrows, validated by `PriceFeed::load`
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds=[b"maker_state",maker.key().as_ref()],
+        bump=maker_state.bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        mut,
+        seeds=[b"pair_registry",mint_a.key().as_ref(),escrow.mint_b.as_ref()],
+        bump=pair_registry.bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        mut,
+        seeds=[b"maker_registry",maker.key().as_ref()],
+        bump=maker_registry.bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    #[account(
+        seeds=[b"config"],
+        bump,
+        constraint=!Config::load(&config)?.take_paused @ EscrowError::TakePaused,
+    )]
+    pub config: UncheckedAccount<'info>,
+    /// Pass to memo each transfer, for destinations with Token-2022's
+    /// `MemoTransfer` extension.
+    pub memo_program: Option<Program<'info, MemoProgram>>,
+    /// The escrow's `ProceedsDestination`, when it has one.
+    #[account(
+        mut,
+        token::mint=mint_b,
+        token::token_program=token_program,
+    )]
+    pub maker_destination_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Any mint_a account the taker owns, in place of `taker_ata_a`.
+    #[account(
+        mut,
+        token::mint=mint_a,
+        token::authority=taker,
+        token::token_program=token_program,
+    )]
+    pub taker_destination_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The front-end's mint_b account, paid `referral_bps` on top of the
+    /// maker's payment.
+    #[account(
+        mut,
+        token::mint=mint_b,
+        token::token_program=token_program,
+    )]
+    pub referrer_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: the taker's token account of the escrow's `TokenGate` mint,
+    /// validated by `check_take`.
+    pub taker_gate: Option<UncheckedAccount<'info>>,
+    /// Counts the taker's fills, for a `MerkleAllowlist` entry with a cap.
+    #[account(
+        init_if_needed,
+        payer=taker,
+        space=TakerFill::DISCRIMINATOR.len() + TakerFill::INIT_SPACE,
+        seeds=[b"taker_fill",escrow.key().as_ref(),taker.key().as_ref()],
+        bump,
+    )]
+    pub taker_fill: Option<Box<Account<'info, TakerFill>>>,
 }
 
 impl<'info> Take<'info> {
-    fn transfer_to_maker(&mut self) -> Result<()> {
+    /// Text memoed before each transfer when `memo_program` is passed: the
+    /// escrow's `Memo` extension, or its seed if it has none.
+    fn memo_text(&self) -> Result<Option<String>> {
+        if self.memo_program.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(
+            match get_extension::<Memo>(&self.escrow.to_account_info())? {
+                Some(memo) => memo.memo,
+                None => format!("escrow {}", self.escrow.seed),
+            },
+        ))
+    }
+    /// Token-2022 only accepts a memo CPI'd right before the transfer.
+    fn memo(&self, text: Option<&str>) -> Result<()> {
+        let (Some(memo_program), Some(text)) = (&self.memo_program, text) else {
+            return Ok(());
+        };
+        build_memo(
+            CpiContext::new(memo_program.to_account_info(), BuildMemo {}),
+            text.as_bytes(),
+        )
+    }
+    /// The escrow's `ProceedsDestination` if it set one, else `maker_ata_b`.
+    fn maker_destination(&self) -> Result<AccountInfo<'info>> {
+        let Some(destination) =
+            get_extension::<ProceedsDestination>(&self.escrow.to_account_info())?
+        else {
+            let ata = self
+                .maker_ata_b
+                .as_ref()
+                .ok_or(EscrowError::MissingTokenAccount)?;
+            return Ok(ata.to_account_info());
+        };
+        let account = self
+            .maker_destination_b
+            .as_ref()
+            .ok_or(EscrowError::InvalidProceedsDestination)?;
+        require_keys_eq!(
+            account.key(),
+            destination.token_account,
+            EscrowError::InvalidProceedsDestination
+        );
+        Ok(account.to_account_info())
+    }
+    fn taker_destination(&self) -> Result<AccountInfo<'info>> {
+        let account = self
+            .taker_destination_a
+            .as_ref()
+            .or(self.taker_ata_a.as_ref())
+            .ok_or(EscrowError::MissingTokenAccount)?;
+        Ok(account.to_account_info())
+    }
+    fn transfer_b(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         transfer_checked(
             CpiContext::new(
                 self.token_program.to_account_info(),
                 TransferChecked {
                     from: self.taker_ata_b.to_account_info(),
-                    to: self.maker_ata_b.to_account_info(),
+                    to,
                     mint: self.mint_b.to_account_info(),
                     authority: self.taker.to_account_info(),
                 },
             ),
-            self.escrow.receive,
+            amount,
             self.mint_b.decimals,
         )?;
         Ok(())
     }
-    fn withdraw_and_close_vault(&mut self) -> Result<()> {
+    /// Pays `amount` to the maker's side. With a `ProceedsSplit`, the
+    /// recipients' token accounts lead `remaining_accounts` in split order.
+    /// Returns the remaining accounts it did not use.
+    fn pay_maker<'a>(
+        &self,
+        amount: u64,
+        remaining_accounts: &'a [AccountInfo<'info>],
+        memo: Option<&str>,
+    ) -> Result<&'a [AccountInfo<'info>]> {
+        let Some(split) = get_extension::<ProceedsSplit>(&self.escrow.to_account_info())? else {
+            self.memo(memo)?;
+            self.transfer_b(self.maker_destination()?, amount)?;
+            return Ok(remaining_accounts);
+        };
+        let accounts = remaining_accounts
+            .get(..split.recipients.len())
+            .ok_or(EscrowError::MissingTokenAccount)?;
+        for ((recipient, account), share) in
+            split.recipients.iter().zip(accounts).zip(split.shares(amount))
+        {
+            require_keys_eq!(
+                account.key(),
+                recipient.token_account,
+                EscrowError::InvalidSplitRecipient
+            );
+            if share == 0 {
+                continue;
+            }
+            self.memo(memo)?;
+            self.transfer_b(account.clone(), share)?;
+        }
+        Ok(&remaining_accounts[accounts.len()..])
+    }
+    /// Pays `royalty` to the NFT's creators, whose mint_b token accounts come
+    /// next in `remaining_accounts` in creator order.
+    fn pay_creators(
+        &self,
+        royalties: &NftRoyalties,
+        royalty: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+        memo: Option<&str>,
+    ) -> Result<()> {
+        if royalty == 0 {
+            return Ok(());
+        }
+        let accounts = remaining_accounts
+            .get(..royalties.creators.len())
+            .ok_or(EscrowError::MissingTokenAccount)?;
+        for ((creator, account), share) in royalties
+            .creators
+            .iter()
+            .zip(accounts)
+            .zip(royalties.shares(royalty))
+        {
+            require_keys_eq!(
+                *account.owner,
+                self.token_program.key(),
+                EscrowError::InvalidRoyaltyRecipient
+            );
+            let token_account = TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+            require!(
+                token_account.owner == creator.address && token_account.mint == self.mint_b.key(),
+                EscrowError::InvalidRoyaltyRecipient
+            );
+            if share == 0 {
+                continue;
+            }
+            self.memo(memo)?;
+            self.transfer_b(account.clone(), share)?;
+        }
+        Ok(())
+    }
+    /// For a `CollectionOffer`, checks the taker's mint_b is one NFT from the
+    /// escrow's collection and that `amount` is what one item fetches.
+    /// Returns the remaining accounts it did not use.
+    fn check_collection_item<'a>(
+        &self,
+        amount: u64,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a [AccountInfo<'info>]> {
+        require_eq!(
+            amount,
+            self.escrow.collection_item_amount()?,
+            EscrowError::InvalidAmount
+        );
+        check_nft(&self.mint_b)?;
+        check_collection_member(
+            &self.mint_b.to_account_info(),
+            &self.escrow.mint_b,
+            remaining_accounts,
+        )
+    }
+    /// Charges the taker `referral_bps` of `owed` for `referrer_b`, capped by
+    /// the config's `max_referral_bps`.
+    fn pay_referrer(&self, owed: u64, referral_bps: u16, memo: Option<&str>) -> Result<()> {
+        if referral_bps == 0 {
+            return Ok(());
+        }
+        let fee = Config::load(&self.config)?.referral_fee(owed, referral_bps)?;
+        let referrer = self
+            .referrer_b
+            .as_ref()
+            .ok_or(EscrowError::MissingTokenAccount)?;
+        if fee == 0 {
+            return Ok(());
+        }
+        self.memo(memo)?;
+        self.transfer_b(referrer.to_account_info(), fee)
+    }
+    /// Holds a capped `MerkleAllowlist` taker to their cap across fills.
+    fn record_taker_fill(&mut self, amount: u64, allowlist: Option<&AllowlistProof>) -> Result<()> {
+        let Some(cap) = allowlist.and_then(|claim| claim.cap) else {
+            return Ok(());
+        };
+        if get_extension::<MerkleAllowlist>(&self.escrow.to_account_info())?.is_none() {
+            return Ok(());
+        }
+        let taker_fill = self
+            .taker_fill
+            .as_mut()
+            .ok_or(EscrowError::MissingTakerFill)?;
+        taker_fill.record(&self.escrow, amount, cap)
+    }
+    /// Sends `amount` out of the vault and, once the order is filled and the
+    /// vault is empty, closes both to the maker. Donated excess keeps the
+    /// escrow open until the maker sweeps it.
+    fn withdraw_and_close_vault(&mut self, amount: u64) -> Result<()> {
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
             self.maker.to_account_info().key.as_ref(),
@@ -92,16 +347,21 @@ impl<'info> Take<'info> {
                 self.token_program.to_account_info(),
                 TransferChecked {
                     from: self.vault.to_account_info(),
-                    to: self.taker_ata_a.to_account_info(),
+                    to: self.taker_destination()?,
                     mint: self.mint_a.to_account_info(),
                     authority: self.escrow.to_account_info(),
                 },
                 &signer_seeds,
             ),
-            self.vault.amount,
+            amount,
             self.mint_a.decimals,
         )?;
 
+        self.vault.reload()?;
+        if self.escrow.remaining() > 0 || self.vault.amount > 0 {
+            return Ok(());
+        }
+
         close_account(CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             CloseAccount {
@@ -111,12 +371,74 @@ impl<'info> Take<'info> {
             },
             &signer_seeds,
         ))?;
+        self.escrow.close(self.maker.to_account_info())?;
+        self.maker_state.close_escrow();
+        self.pair_registry.remove(&self.escrow.key());
+        self.maker_registry.remove(&self.escrow.key());
+        Ok(())
+    }
+    /// Pays for and delivers `amount` of mint_a at the current rate, with the
+    /// taker's side rounded up.
+    pub(crate) fn fill(
+        &mut self,
+        amount: u64,
+        referral_bps: u16,
+        allowlist: Option<&AllowlistProof>,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        check_take(
+            &self.escrow.to_account_info(),
+            &self.taker.key(),
+            now,
+            self.taker_gate.as_ref().map(|gate| gate.as_ref()),
+            allowlist,
+        )?;
+        self.record_taker_fill(amount, allowlist)?;
+        let collection_offer =
+            get_extension::<CollectionOffer>(&self.escrow.to_account_info())?.is_some();
+        let remaining_accounts = if collection_offer {
+            self.check_collection_item(amount, remaining_accounts)?
+        } else {
+            remaining_accounts
+        };
+        self.escrow.record_fill(amount, now)?;
+        // Each fill of a collection offer buys exactly one NFT
+        let owed = if collection_offer {
+            1
+        } else {
+            let price = self.escrow.current_price(
+                self.price_feed.as_deref(),
+                self.mint_a.decimals,
+                self.mint_b.decimals,
+            )?;
+            price.amount_b_for(amount, Rounding::Up)?
+        };
+        let memo = self.memo_text()?;
+        let royalties = get_extension::<NftRoyalties>(&self.escrow.to_account_info())?;
+        let royalty = royalties.as_ref().map_or(0, |r| r.royalty(owed));
+        let remaining_accounts = self.pay_maker(owed - royalty, remaining_accounts, memo.as_deref())?;
+        if let Some(royalties) = &royalties {
+            self.pay_creators(royalties, royalty, remaining_accounts, memo.as_deref())?;
+        }
+        self.pay_referrer(owed, referral_bps, memo.as_deref())?;
+        self.memo(memo.as_deref())?;
+        self.withdraw_and_close_vault(amount)?;
         Ok(())
     }
 }
 
-pub fn handler(ctx: Context<Take>) -> Result<()> {
-    ctx.accounts.transfer_to_maker()?;
-    ctx.accounts.withdraw_and_close_vault()?;
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+    referral_bps: u16,
+    allowlist: Option<AllowlistProof>,
+) -> Result<()> {
+    let amount = ctx.accounts.escrow.remaining();
+    ctx.accounts.fill(
+        amount,
+        referral_bps,
+        allowlist.as_ref(),
+        ctx.remaining_accounts,
+    )?;
     Ok(())
 }
\ No newline at end of file
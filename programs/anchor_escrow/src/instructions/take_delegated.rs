@@ -0,0 +1,167 @@
+use crate::errors::EscrowError;
+use crate::extension::check_take;
+use crate::state::{Config, Escrow, EscrowKind, MakerState, Registry};
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct TakeDelegated<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = mint_b @ EscrowError::InvalidMintB,
+        constraint = escrow.kind == EscrowKind::Delegated @ EscrowError::InvalidEscrowKind,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        mut,
+        seeds = [b"pair_registry", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        mut,
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump = maker_registry.bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !Config::load(&config)?.take_paused @ EscrowError::TakePaused,
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+impl<'info> TakeDelegated<'info> {
+    /// The maker can revoke, re-approve or spend `maker_ata_a` at any time after
+    /// `make_delegated`, so the order is only fillable while both hold.
+    fn check_allowance(&self) -> Result<()> {
+        require!(
+            self.maker_ata_a.delegate == COption::Some(self.escrow.key())
+                && self.maker_ata_a.delegated_amount >= self.escrow.deposited,
+            EscrowError::InsufficientAllowance
+        );
+        require_gte!(
+            self.maker_ata_a.amount,
+            self.escrow.deposited,
+            EscrowError::InsufficientBalance
+        );
+        Ok(())
+    }
+    fn transfer_to_maker(&mut self) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.taker_ata_b.to_account_info(),
+                    to: self.maker_ata_b.to_account_info(),
+                    mint: self.mint_b.to_account_info(),
+                    authority: self.taker.to_account_info(),
+                },
+            ),
+            self.escrow.receive,
+            self.mint_b.decimals,
+        )?;
+        Ok(())
+    }
+    fn withdraw_from_maker(&mut self) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    to: self.taker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            self.escrow.deposited,
+            self.mint_a.decimals,
+        )?;
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<TakeDelegated>) -> Result<()> {
+    ctx.accounts.check_allowance()?;
+    let now = Clock::get()?.unix_timestamp;
+    check_take(
+        &ctx.accounts.escrow.to_account_info(),
+        &ctx.accounts.taker.key(),
+        now,
+        None,
+        None,
+    )?;
+    let deposited = ctx.accounts.escrow.deposited;
+    ctx.accounts.escrow.record_fill(deposited, now)?;
+    ctx.accounts.transfer_to_maker()?;
+    ctx.accounts.withdraw_from_maker()?;
+    ctx.accounts.maker_state.close_escrow();
+    let escrow = ctx.accounts.escrow.key();
+    ctx.accounts.pair_registry.remove(&escrow);
+    ctx.accounts.maker_registry.remove(&escrow);
+    Ok(())
+}
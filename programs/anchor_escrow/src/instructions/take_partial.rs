@@ -0,0 +1,21 @@
+use crate::extension::AllowlistProof;
+use crate::instructions::Take;
+use anchor_lang::prelude::*;
+
+/// Takes `amount` of mint_a out of the vault, paying for it at the escrow's
+/// rate. The escrow stays open until all of `deposited` is filled;
+/// `Escrow::record_fill` rejects zero or more than what remains.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+    amount: u64,
+    referral_bps: u16,
+    allowlist: Option<AllowlistProof>,
+) -> Result<()> {
+    ctx.accounts.fill(
+        amount,
+        referral_bps,
+        allowlist.as_ref(),
+        ctx.remaining_accounts,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use crate::errors::EscrowError;
+use crate::state::{Config, EscrowZeroCopy, MakerState, Registry};
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+#[derive(Accounts)]
+pub struct TakeZeroCopy<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"escrow_zc", maker.key().as_ref(), escrow.load()?.seed.to_le_bytes().as_ref()],
+        bump = escrow.load()?.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = mint_b @ EscrowError::InvalidMintB,
+    )]
+    pub escrow: AccountLoader<'info, EscrowZeroCopy>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+    #[account(
+        mut,
+        seeds = [b"pair_registry", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = pair_registry.bump,
+    )]
+    pub pair_registry: Box<Account<'info, Registry>>,
+    #[account(
+        mut,
+        seeds = [b"maker_registry", maker.key().as_ref()],
+        bump = maker_registry.bump,
+    )]
+    pub maker_registry: Box<Account<'info, Registry>>,
+    /// CHECK: may not be initialized yet; read through `Config::load`.
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !Config::load(&config)?.take_paused @ EscrowError::TakePaused,
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+impl<'info> TakeZeroCopy<'info> {
+    fn transfer_to_maker(&mut self, amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.taker_ata_b.to_account_info(),
+                    to: self.maker_ata_b.to_account_info(),
+                    mint: self.mint_b.to_account_info(),
+                    authority: self.taker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_b.decimals,
+        )?;
+        Ok(())
+    }
+    /// Same rules as `Take`: donated excess keeps the escrow open until the
+    /// maker refunds it.
+    fn withdraw_and_close_vault(&mut self, escrow: &EscrowZeroCopy) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow_zc",
+            self.maker.to_account_info().key.as_ref(),
+            &escrow.seed.to_le_bytes()[..],
+            &[escrow.bump],
+        ]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.taker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            escrow.remaining(),
+            self.mint_a.decimals,
+        )?;
+
+        self.vault.reload()?;
+        if self.vault.amount > 0 {
+            return Ok(());
+        }
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination: self.maker.to_account_info(),
+            },
+            &signer_seeds,
+        ))?;
+        self.escrow.close(self.maker.to_account_info())?;
+        self.maker_state.close_escrow();
+        self.pair_registry.remove(&self.escrow.key());
+        self.maker_registry.remove(&self.escrow.key());
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<TakeZeroCopy>) -> Result<()> {
+    // Copy out so no borrow of the account data is held across the CPIs
+    let escrow = *ctx.accounts.escrow.load()?;
+    require_gt!(escrow.remaining(), 0, EscrowError::EscrowAlreadyFilled);
+
+    ctx.accounts.escrow.load_mut()?.filled = escrow.deposited;
+    ctx.accounts.transfer_to_maker(escrow.receive)?;
+    ctx.accounts.withdraw_and_close_vault(&escrow)?;
+    Ok(())
+}
@@ -1,23 +1,201 @@
-#![allow(unexpected_cfgs)]
+#![allow(unexpected_cfgs, ambiguous_glob_reexports)]
 
 use anchor_lang::prelude::*;
 pub mod errors;
+pub mod extension;
 pub mod instructions;
 pub use instructions::*;
+pub mod nft;
+pub mod oracle;
+pub mod price;
 pub mod state;
+pub mod token_ext;
 
 declare_id!("BzHmWns7farc1UMjxAzqjb684g43B9cv8ZHCXzwxJAxr");
 
 #[program]
 pub mod anchor_escrow {
     use super::*;
-    pub fn make(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
-        instructions::make::handler(ctx, seed, receive, amount)
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        receive: u64,
+        amount: u64,
+        extensions: Vec<extension::EscrowExtension>,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make::handler(ctx, seed, receive, amount, extensions, accepted_hazards)
     }
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        instructions::take::handler(ctx)
+    pub fn make_next(
+        ctx: Context<Make>,
+        seed: u64,
+        receive: u64,
+        amount: u64,
+        extensions: Vec<extension::EscrowExtension>,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_next::handler(ctx, seed, receive, amount, extensions, accepted_hazards)
+    }
+    pub fn make_oracle(
+        ctx: Context<Make>,
+        seed: u64,
+        amount: u64,
+        oracle: oracle::OracleConfig,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_oracle::handler(ctx, seed, amount, oracle, accepted_hazards)
+    }
+    pub fn make_priced(
+        ctx: Context<Make>,
+        seed: u64,
+        amount: u64,
+        price_numerator: u64,
+        price_denominator: u64,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_priced::handler(
+            ctx,
+            seed,
+            amount,
+            price_numerator,
+            price_denominator,
+            accepted_hazards,
+        )
+    }
+    pub fn make_ui_amount(
+        ctx: Context<Make>,
+        seed: u64,
+        receive_ui: u64,
+        amount: u64,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_ui_amount::handler(ctx, seed, receive_ui, amount, accepted_hazards)
+    }
+    pub fn make_nft(
+        ctx: Context<Make>,
+        seed: u64,
+        receive: u64,
+        collection: Option<Pubkey>,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_nft::handler(ctx, seed, receive, collection, accepted_hazards)
+    }
+    pub fn make_collection_offer(
+        ctx: Context<Make>,
+        seed: u64,
+        amount: u64,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_collection_offer::handler(ctx, seed, amount, accepted_hazards)
+    }
+    pub fn make_collection_bid(
+        ctx: Context<Make>,
+        seed: u64,
+        price: u64,
+        quantity: u64,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_collection_bid::handler(ctx, seed, price, quantity, accepted_hazards)
+    }
+    pub fn take<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+        referral_bps: u16,
+        allowlist: Option<extension::AllowlistProof>,
+    ) -> Result<()> {
+        instructions::take::handler(ctx, referral_bps, allowlist)
+    }
+    pub fn take_partial<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+        amount: u64,
+        referral_bps: u16,
+        allowlist: Option<extension::AllowlistProof>,
+    ) -> Result<()> {
+        instructions::take_partial::handler(ctx, amount, referral_bps, allowlist)
+    }
+    pub fn sell_into_bid<'info>(ctx: Context<'_, '_, 'info, 'info, Take<'info>>) -> Result<()> {
+        instructions::sell_into_bid::handler(ctx)
+    }
+    pub fn quote(
+        ctx: Context<Quote>,
+        amount: Option<u64>,
+        referral_bps: u16,
+    ) -> Result<TakeQuote> {
+        instructions::quote::handler(ctx, amount, referral_bps)
     }
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         instructions::refund::handler(ctx)
     }
+    pub fn sweep_excess(ctx: Context<SweepExcess>) -> Result<()> {
+        instructions::sweep_excess::handler(ctx)
+    }
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>) -> Result<()> {
+        instructions::migrate_escrow::handler(ctx)
+    }
+    pub fn make_zero_copy(
+        ctx: Context<MakeZeroCopy>,
+        seed: u64,
+        receive: u64,
+        amount: u64,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_zero_copy::handler(ctx, seed, receive, amount, accepted_hazards)
+    }
+    pub fn take_zero_copy(ctx: Context<TakeZeroCopy>) -> Result<()> {
+        instructions::take_zero_copy::handler(ctx)
+    }
+    pub fn refund_zero_copy(ctx: Context<RefundZeroCopy>) -> Result<()> {
+        instructions::refund_zero_copy::handler(ctx)
+    }
+    pub fn add_extensions(
+        ctx: Context<AddExtensions>,
+        extensions: Vec<extension::EscrowExtension>,
+    ) -> Result<()> {
+        instructions::add_extensions::handler(ctx, extensions)
+    }
+    pub fn make_delegated(
+        ctx: Context<MakeDelegated>,
+        seed: u64,
+        receive: u64,
+        amount: u64,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::make_delegated::handler(ctx, seed, receive, amount, accepted_hazards)
+    }
+    pub fn take_delegated(ctx: Context<TakeDelegated>) -> Result<()> {
+        instructions::take_delegated::handler(ctx)
+    }
+    pub fn cancel_delegated(ctx: Context<CancelDelegated>) -> Result<()> {
+        instructions::cancel_delegated::handler(ctx)
+    }
+    pub fn fill_signed_order(
+        ctx: Context<FillSignedOrder>,
+        order: SignedOrder,
+        accepted_hazards: u8,
+    ) -> Result<()> {
+        instructions::fill_signed_order::handler(ctx, order, accepted_hazards)
+    }
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        instructions::initialize_config::handler(ctx)
+    }
+    pub fn set_pause(ctx: Context<SetPause>, make_paused: bool, take_paused: bool) -> Result<()> {
+        instructions::set_pause::handler(ctx, make_paused, take_paused)
+    }
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin::handler(ctx, new_admin)
+    }
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin::handler(ctx)
+    }
+    pub fn set_allowlist_only(ctx: Context<SetAllowlistOnly>, allowlist_only: bool) -> Result<()> {
+        instructions::set_allowlist_only::handler(ctx, allowlist_only)
+    }
+    pub fn set_mint_listing(ctx: Context<SetMintListing>, status: state::MintStatus) -> Result<()> {
+        instructions::set_mint_listing::handler(ctx, status)
+    }
+    pub fn remove_mint_listing(ctx: Context<RemoveMintListing>) -> Result<()> {
+        instructions::remove_mint_listing::handler(ctx)
+    }
+    pub fn set_referral_cap(ctx: Context<SetReferralCap>, max_referral_bps: u16) -> Result<()> {
+        instructions::set_referral_cap::handler(ctx, max_referral_bps)
+    }
 }
\ No newline at end of file
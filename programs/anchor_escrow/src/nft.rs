@@ -0,0 +1,332 @@
+//! NFT checks and metadata for NFT escrows. Royalties and collections come
+//! from the Token-2022 metadata and group-member extensions on the mint or,
+//! with the `metaplex` feature, from a Metaplex Token Metadata account.
+use crate::errors::EscrowError;
+use crate::extension::{NftRoyalties, RoyaltyCreator};
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_2022_extensions::spl_token_metadata_interface::state::TokenMetadata;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::Mint;
+use spl_token_group_interface::state::TokenGroupMember;
+
+/// Additional-metadata key holding the royalty in basis points. Every other
+/// key that parses as a pubkey names a creator, with its percent share as the
+/// value, as in the WNS convention.
+pub const ROYALTY_BPS_KEY: &str = "royalty_basis_points";
+
+/// An NFT has 0 decimals and exactly one token in existence.
+fn is_nft(mint: &Mint) -> bool {
+    mint.decimals == 0 && mint.supply == 1
+}
+
+pub fn check_nft(mint: &Mint) -> Result<()> {
+    require!(is_nft(mint), EscrowError::NotAnNft);
+    Ok(())
+}
+
+/// Every make but `make_nft` refuses an NFT as mint_a, since it would sell
+/// the NFT without paying its creators.
+pub fn check_not_nft(mint: &Mint) -> Result<()> {
+    require!(!is_nft(mint), EscrowError::NftNeedsMakeNft);
+    Ok(())
+}
+
+/// Royalties for `mint`. With the `metaplex` feature, a Token Metadata account
+/// passed first in `remaining_accounts` is read instead of the mint itself.
+/// SPL Token mints keep their royalties only in Token Metadata, so they are
+/// refused without it rather than read as paying none.
+#[cfg_attr(not(feature = "metaplex"), allow(unused_variables))]
+pub fn read_royalties(
+    mint: &AccountInfo,
+    remaining_accounts: &[AccountInfo],
+) -> Result<NftRoyalties> {
+    #[cfg(feature = "metaplex")]
+    if let Some(metadata) = metaplex::metadata_account(remaining_accounts) {
+        return metaplex::Metadata::load(metadata, mint.key)?.royalties();
+    }
+    require_keys_neq!(*mint.owner, spl_token::ID, EscrowError::MissingNftMetadata);
+    token_2022_royalties(mint)
+}
+
+/// Fails unless `mint` is a member of `collection`: a Token-2022 group member
+/// of that group or, with the `metaplex` feature, an NFT whose Token Metadata
+/// account, passed first in `remaining_accounts`, names it as a verified
+/// collection. Returns the remaining accounts it did not use.
+pub fn check_collection_member<'a, 'info>(
+    mint: &AccountInfo,
+    collection: &Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a [AccountInfo<'info>]> {
+    #[cfg(feature = "metaplex")]
+    if let Some(metadata) = metaplex::metadata_account(remaining_accounts) {
+        let member_of = metaplex::Metadata::load(metadata, mint.key)?.verified_collection();
+        require!(member_of == Some(*collection), EscrowError::NotInCollection);
+        return Ok(&remaining_accounts[1..]);
+    }
+    require!(
+        token_2022_group(mint)? == Some(*collection),
+        EscrowError::NotInCollection
+    );
+    Ok(remaining_accounts)
+}
+
+/// Reads the `TokenMetadata` stored on a Token-2022 mint. Mints without
+/// on-mint metadata, or without a royalty entry, pay no royalties.
+pub fn token_2022_royalties(mint: &AccountInfo) -> Result<NftRoyalties> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(NftRoyalties::default());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(metadata) = state.get_variable_len_extension::<TokenMetadata>() else {
+        return Ok(NftRoyalties::default());
+    };
+    royalties_from_fields(&metadata.additional_metadata)
+}
+
+/// The group a Token-2022 mint's `TokenGroupMember` extension puts it in.
+/// Token-2022 only adds members with the group's update authority signing.
+fn token_2022_group(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(member) = state.get_extension::<TokenGroupMember>() else {
+        return Ok(None);
+    };
+    Ok((member.mint == *mint.key).then_some(member.group))
+}
+
+fn royalties_from_fields(fields: &[(String, String)]) -> Result<NftRoyalties> {
+    let mut royalties = NftRoyalties::default();
+    for (key, value) in fields {
+        if key == ROYALTY_BPS_KEY {
+            royalties.royalty_bps = value
+                .parse()
+                .map_err(|_| EscrowError::InvalidRoyaltyMetadata)?;
+        } else if let Ok(address) = key.parse::<Pubkey>() {
+            let share = value
+                .parse()
+                .map_err(|_| EscrowError::InvalidRoyaltyMetadata)?;
+            royalties.creators.push(RoyaltyCreator { address, share });
+        }
+    }
+    royalties.validate()?;
+    Ok(royalties)
+}
+
+#[cfg(feature = "metaplex")]
+pub mod metaplex {
+    use super::*;
+    use anchor_lang::solana_program::pubkey;
+
+    pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+        pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+    const METADATA_V1: u8 = 4;
+
+    /// The first remaining account, if the Token Metadata program owns it.
+    pub fn metadata_account<'a, 'info>(
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Option<&'a AccountInfo<'info>> {
+        remaining_accounts
+            .first()
+            .filter(|account| account.owner == &TOKEN_METADATA_PROGRAM_ID)
+    }
+
+    #[derive(AnchorDeserialize)]
+    struct Creator {
+        address: Pubkey,
+        _verified: bool,
+        share: u8,
+    }
+
+    #[derive(AnchorDeserialize)]
+    struct Collection {
+        verified: bool,
+        key: Pubkey,
+    }
+
+    /// The leading fields of a Token Metadata account, up to the collection.
+    #[derive(AnchorDeserialize)]
+    pub struct Metadata {
+        key: u8,
+        _update_authority: Pubkey,
+        mint: Pubkey,
+        _name: String,
+        _symbol: String,
+        _uri: String,
+        seller_fee_basis_points: u16,
+        creators: Option<Vec<Creator>>,
+        _primary_sale_happened: bool,
+        _is_mutable: bool,
+        _edition_nonce: Option<u8>,
+        _token_standard: Option<u8>,
+        collection: Option<Collection>,
+    }
+
+    impl Metadata {
+        /// Reads `mint`'s metadata PDA.
+        pub fn load(metadata: &AccountInfo, mint: &Pubkey) -> Result<Metadata> {
+            let (expected, _) = Pubkey::find_program_address(
+                &[
+                    b"metadata",
+                    TOKEN_METADATA_PROGRAM_ID.as_ref(),
+                    mint.as_ref(),
+                ],
+                &TOKEN_METADATA_PROGRAM_ID,
+            );
+            require_keys_eq!(
+                metadata.key(),
+                expected,
+                EscrowError::InvalidRoyaltyMetadata
+            );
+            require_keys_eq!(
+                *metadata.owner,
+                TOKEN_METADATA_PROGRAM_ID,
+                EscrowError::InvalidRoyaltyMetadata
+            );
+            Self::parse(&metadata.try_borrow_data()?, mint)
+        }
+
+        fn parse(data: &[u8], mint: &Pubkey) -> Result<Metadata> {
+            let metadata = Metadata::deserialize(&mut &data[..])
+                .map_err(|_| EscrowError::InvalidRoyaltyMetadata)?;
+            require!(
+                metadata.key == METADATA_V1 && metadata.mint == *mint,
+                EscrowError::InvalidRoyaltyMetadata
+            );
+            Ok(metadata)
+        }
+
+        /// `seller_fee_basis_points` split among the creators.
+        pub fn royalties(self) -> Result<NftRoyalties> {
+            let royalties = NftRoyalties {
+                royalty_bps: self.seller_fee_basis_points,
+                creators: self
+                    .creators
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|c| RoyaltyCreator {
+                        address: c.address,
+                        share: c.share,
+                    })
+                    .collect(),
+            };
+            royalties.validate()?;
+            Ok(royalties)
+        }
+
+        /// The collection, once its authority has verified the NFT.
+        pub fn verified_collection(&self) -> Option<Pubkey> {
+            self.collection
+                .as_ref()
+                .filter(|c| c.verified)
+                .map(|c| c.key)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn metadata_v1(
+            mint: &Pubkey,
+            creator: &Pubkey,
+            collection: Option<(bool, Pubkey)>,
+        ) -> Vec<u8> {
+            let mut data = vec![METADATA_V1];
+            data.extend_from_slice(Pubkey::new_unique().as_ref());
+            data.extend_from_slice(mint.as_ref());
+            for text in ["Name", "SYM", "https://example.com"] {
+                data.extend_from_slice(&text.to_string().try_to_vec().unwrap());
+            }
+            data.extend_from_slice(&250u16.to_le_bytes());
+            data.extend_from_slice(&[1, 1, 0, 0, 0]);
+            data.extend_from_slice(creator.as_ref());
+            data.extend_from_slice(&[1, 100]);
+            // primary_sale_happened, is_mutable, no edition nonce, NonFungible
+            data.extend_from_slice(&[0, 1, 0, 1, 0]);
+            data.extend_from_slice(
+                &collection
+                    .map(|(v, k)| (v, k.to_bytes()))
+                    .try_to_vec()
+                    .unwrap(),
+            );
+            // Later fields are ignored
+            data.extend_from_slice(&[0; 8]);
+            data
+        }
+
+        #[test]
+        fn parses_metadata_v1_prefix() {
+            let (mint, creator, collection) = (
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            );
+            let data = metadata_v1(&mint, &creator, Some((true, collection)));
+
+            let metadata = Metadata::parse(&data, &mint).unwrap();
+            assert_eq!(metadata.verified_collection(), Some(collection));
+            let royalties = metadata.royalties().unwrap();
+            assert_eq!(royalties.royalty_bps, 250);
+            assert_eq!(royalties.creators[0].address, creator);
+            assert!(Metadata::parse(&data, &Pubkey::new_unique()).is_err());
+        }
+
+        #[test]
+        fn unverified_collection_is_ignored() {
+            let mint = Pubkey::new_unique();
+            let data = metadata_v1(
+                &mint,
+                &Pubkey::new_unique(),
+                Some((false, Pubkey::new_unique())),
+            );
+            assert_eq!(
+                Metadata::parse(&data, &mint).unwrap().verified_collection(),
+                None
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn royalties_follow_wns_fields() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let royalties = royalties_from_fields(&[
+            field("artist", "someone"),
+            field(ROYALTY_BPS_KEY, "500"),
+            field(&a.to_string(), "70"),
+            field(&b.to_string(), "30"),
+        ])
+        .unwrap();
+
+        assert_eq!(royalties.royalty_bps, 500);
+        assert_eq!(royalties.creators.len(), 2);
+        assert_eq!(royalties.royalty(10_001), 500);
+        assert_eq!(royalties.shares(500), vec![350, 150]);
+    }
+
+    #[test]
+    fn royalty_shares_must_total_100() {
+        let err = royalties_from_fields(&[
+            field(ROYALTY_BPS_KEY, "500"),
+            field(&Pubkey::new_unique().to_string(), "90"),
+        ])
+        .err();
+        assert_eq!(err, Some(EscrowError::InvalidRoyaltyMetadata.into()));
+    }
+}
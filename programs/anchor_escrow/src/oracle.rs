@@ -0,0 +1,104 @@
+use crate::errors::EscrowError;
+use crate::price::{pow10, Price};
+use anchor_lang::{prelude::*, solana_program::pubkey};
+
+/// Owner of the price accounts `make_oracle` escrows may reference: the Pyth
+/// oracle program of the cluster the build targets. Only `localnet` builds,
+/// used by the LiteSVM tests, trust the bundled `mock_oracle`, whose
+/// `set_price` anyone can call.
+#[cfg(feature = "localnet")]
+pub const ORACLE_PROGRAM_ID: Pubkey = pubkey!("6r2E4hFibeJbkFagfqJMh7vvqhj45edf3HKTLpen2P2s");
+#[cfg(all(feature = "devnet", not(feature = "localnet")))]
+pub const ORACLE_PROGRAM_ID: Pubkey = pubkey!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s");
+#[cfg(not(any(feature = "devnet", feature = "localnet")))]
+pub const ORACLE_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+const MAGIC: u32 = 0xa1b2c3d4;
+const VERSION: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+/// Aggregate status a price must carry to be used.
+const STATUS_TRADING: u32 = 1;
+/// Size of a Pyth legacy price account: 240 bytes of header and aggregate
+/// followed by 32 publisher components of 96 bytes each.
+const PRICE_ACCOUNT_LEN: usize = 240 + 32 * 96;
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Maker-set terms for an escrow priced from an oracle at take time.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct OracleConfig {
+    /// Price of one mint_a in mint_b, Pyth-style layout.
+    pub price_feed: Pubkey,
+    /// Premium on top of the oracle price charged to the taker.
+    pub spread_bps: u16,
+    /// Oldest `publish_time` accepted, in seconds before the take.
+    pub max_staleness: u32,
+    /// Widest confidence interval accepted, relative to the price.
+    pub max_confidence_bps: u16,
+}
+
+/// Subset of a Pyth legacy price account the escrow reads: `expo` at 20,
+/// the `timestamp` of the last aggregate at 96, and the aggregate price,
+/// conf and status at 208, 216 and 224.
+pub struct PriceFeed {
+    pub expo: i32,
+    pub price: i64,
+    pub conf: u64,
+    pub publish_time: i64,
+}
+
+impl PriceFeed {
+    pub fn load(config: &OracleConfig, account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(account.key(), config.price_feed, EscrowError::InvalidPriceFeed);
+        require_keys_eq!(*account.owner, ORACLE_PROGRAM_ID, EscrowError::InvalidPriceFeed);
+
+        let data = account.try_borrow_data()?;
+        require_gte!(data.len(), PRICE_ACCOUNT_LEN, EscrowError::InvalidPriceFeed);
+        let read_u32 = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+        let read_u64 = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+        require!(
+            read_u32(0) == MAGIC && read_u32(4) == VERSION && read_u32(8) == ACCOUNT_TYPE_PRICE,
+            EscrowError::InvalidPriceFeed
+        );
+        require_eq!(read_u32(224), STATUS_TRADING, EscrowError::OraclePriceNotTrading);
+
+        Ok(Self {
+            expo: read_u32(20) as i32,
+            price: read_u64(208) as i64,
+            conf: read_u64(216),
+            publish_time: read_u64(96) as i64,
+        })
+    }
+
+    pub fn validate(&self, config: &OracleConfig, now: i64) -> Result<()> {
+        require_gt!(self.price, 0, EscrowError::InvalidOraclePrice);
+        let age = now.saturating_sub(self.publish_time);
+        require!(
+            age <= i64::from(config.max_staleness),
+            EscrowError::StalePrice
+        );
+        require!(
+            (self.conf as u128) * BPS_DENOMINATOR
+                <= (self.price as u128) * u128::from(config.max_confidence_bps),
+            EscrowError::PriceConfidenceTooWide
+        );
+        Ok(())
+    }
+
+    /// Rate the taker pays with the maker's spread applied, normalised to
+    /// base units of both mints.
+    pub fn price(&self, config: &OracleConfig, decimals_a: u8, decimals_b: u8) -> Result<Price> {
+        let expo = u8::try_from(self.expo.unsigned_abs()).map_err(|_| EscrowError::MathOverflow)?;
+        let (expo_b, expo_a) = if self.expo >= 0 { (expo, 0) } else { (0, expo) };
+        let scale_b = pow10(decimals_b.checked_add(expo_b).ok_or(EscrowError::MathOverflow)?)?;
+        let scale_a = pow10(decimals_a.checked_add(expo_a).ok_or(EscrowError::MathOverflow)?)?;
+
+        let numerator = (self.price as u128)
+            .checked_mul(BPS_DENOMINATOR + u128::from(config.spread_bps))
+            .and_then(|n| n.checked_mul(scale_b))
+            .ok_or(EscrowError::MathOverflow)?;
+        let denominator = BPS_DENOMINATOR
+            .checked_mul(scale_a)
+            .ok_or(EscrowError::MathOverflow)?;
+        Price::new(numerator, denominator)
+    }
+}
@@ -0,0 +1,250 @@
+use crate::errors::EscrowError;
+use anchor_lang::prelude::*;
+
+/// Which side absorbs the remainder when a conversion is not exact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Used for what the taker pays, so the maker is never short-changed.
+    Up,
+    /// Used for what the taker receives for a given payment.
+    Down,
+}
+
+/// Exchange rate in base units: `numerator` mint_b for every `denominator`
+/// mint_a. Always kept reduced so the checked math overflows as late as
+/// possible.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct Price {
+    pub numerator: u128,
+    pub denominator: u128,
+}
+
+impl Price {
+    pub fn new(numerator: u128, denominator: u128) -> Result<Self> {
+        require!(numerator > 0 && denominator > 0, EscrowError::InvalidPrice);
+        let divisor = gcd(numerator, denominator);
+        Ok(Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        })
+    }
+
+    /// `receive` mint_b for `amount` mint_a, both already in base units.
+    pub fn from_amounts(receive: u64, amount: u64) -> Result<Self> {
+        Self::new(receive.into(), amount.into())
+    }
+
+    /// `ui_numerator` whole mint_b for `ui_denominator` whole mint_a,
+    /// normalised by each mint's `decimals`.
+    pub fn from_ui(
+        ui_numerator: u64,
+        ui_denominator: u64,
+        decimals_a: u8,
+        decimals_b: u8,
+    ) -> Result<Self> {
+        let numerator = u128::from(ui_numerator)
+            .checked_mul(pow10(decimals_b)?)
+            .ok_or(EscrowError::MathOverflow)?;
+        let denominator = u128::from(ui_denominator)
+            .checked_mul(pow10(decimals_a)?)
+            .ok_or(EscrowError::MathOverflow)?;
+        Self::new(numerator, denominator)
+    }
+
+    /// mint_b owed for `amount_a` mint_a.
+    pub fn amount_b_for(&self, amount_a: u64, rounding: Rounding) -> Result<u64> {
+        mul_div(amount_a, self.numerator, self.denominator, rounding)
+    }
+
+    /// mint_a that `amount_b` mint_b buys.
+    pub fn amount_a_for(&self, amount_b: u64, rounding: Rounding) -> Result<u64> {
+        mul_div(amount_b, self.denominator, self.numerator, rounding)
+    }
+}
+
+pub fn pow10(exponent: u8) -> Result<u128> {
+    10u128
+        .checked_pow(exponent.into())
+        .ok_or(error!(EscrowError::MathOverflow))
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// `value * numerator / denominator`, cancelling common factors of `value`
+/// and `denominator` before multiplying.
+fn mul_div(value: u64, numerator: u128, denominator: u128, rounding: Rounding) -> Result<u64> {
+    require!(denominator > 0, EscrowError::InvalidPrice);
+    let divisor = gcd(value.into(), denominator);
+    let value = u128::from(value) / divisor;
+    let denominator = denominator / divisor;
+
+    let product = value
+        .checked_mul(numerator)
+        .ok_or(EscrowError::MathOverflow)?;
+    let result = match rounding {
+        Rounding::Up => product.div_ceil(denominator),
+        Rounding::Down => product / denominator,
+    };
+    u64::try_from(result).map_err(|_| error!(EscrowError::MathOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_overflow(result: Result<u64>) -> bool {
+        result.unwrap_err() == EscrowError::MathOverflow.into()
+    }
+
+    #[test]
+    fn new_reduces_and_rejects_zero() {
+        assert_eq!(
+            Price::new(500, 1_000).unwrap(),
+            Price { numerator: 1, denominator: 2 }
+        );
+        assert_eq!(
+            Price::new(0, 1).unwrap_err(),
+            EscrowError::InvalidPrice.into()
+        );
+        assert_eq!(
+            Price::new(1, 0).unwrap_err(),
+            EscrowError::InvalidPrice.into()
+        );
+    }
+
+    #[test]
+    fn from_amounts_round_trips_full_fill() {
+        for (receive, amount) in [(1, 1), (500_000_000, 1_000_000_000), (7, 3), (u64::MAX, 1), (1, u64::MAX), (u64::MAX, u64::MAX - 1)] {
+            let price = Price::from_amounts(receive, amount).unwrap();
+            assert_eq!(price.amount_b_for(amount, Rounding::Up).unwrap(), receive);
+            assert_eq!(price.amount_b_for(amount, Rounding::Down).unwrap(), receive);
+            assert_eq!(price.amount_a_for(receive, Rounding::Down).unwrap(), amount);
+        }
+    }
+
+    #[test]
+    fn from_ui_normalises_decimals() {
+        // 2.5 mint_b (6 decimals) per 1 mint_a (9 decimals)
+        let price = Price::from_ui(5, 2, 9, 6).unwrap();
+        assert_eq!(price, Price { numerator: 1, denominator: 400 });
+        assert_eq!(price.amount_b_for(1_000_000_000, Rounding::Up).unwrap(), 2_500_000);
+        assert_eq!(price.amount_a_for(2_500_000, Rounding::Down).unwrap(), 1_000_000_000);
+
+        // Same decimals on both sides is a plain ratio
+        let price = Price::from_ui(3, 4, 6, 6).unwrap();
+        assert_eq!(price, Price { numerator: 3, denominator: 4 });
+
+        // 0-decimal mint_a priced in a 9-decimal mint_b
+        let price = Price::from_ui(1, 1, 0, 9).unwrap();
+        assert_eq!(price.amount_b_for(3, Rounding::Up).unwrap(), 3_000_000_000);
+    }
+
+    #[test]
+    fn from_ui_overflow() {
+        // 10^39 does not fit in u128
+        assert_eq!(
+            Price::from_ui(1, 1, 39, 0).unwrap_err(),
+            EscrowError::MathOverflow.into()
+        );
+        assert_eq!(
+            Price::from_ui(1, 1, 0, u8::MAX).unwrap_err(),
+            EscrowError::MathOverflow.into()
+        );
+        // u64::MAX * 10^20 overflows u128
+        assert_eq!(
+            Price::from_ui(u64::MAX, 1, 0, 20).unwrap_err(),
+            EscrowError::MathOverflow.into()
+        );
+        // ...while the largest exponent that fits still works
+        assert!(Price::from_ui(u64::MAX, 1, 0, 19).is_ok());
+    }
+
+    #[test]
+    fn rounding_favours_maker() {
+        // 1 mint_b per 3 mint_a
+        let price = Price::from_amounts(1, 3).unwrap();
+        assert_eq!(price.amount_b_for(1, Rounding::Up).unwrap(), 1);
+        assert_eq!(price.amount_b_for(1, Rounding::Down).unwrap(), 0);
+        assert_eq!(price.amount_b_for(2, Rounding::Up).unwrap(), 1);
+        assert_eq!(price.amount_b_for(3, Rounding::Up).unwrap(), 1);
+        assert_eq!(price.amount_b_for(4, Rounding::Up).unwrap(), 2);
+        assert_eq!(price.amount_a_for(1, Rounding::Down).unwrap(), 3);
+
+        // 3 mint_b per 2 mint_a
+        let price = Price::from_amounts(3, 2).unwrap();
+        assert_eq!(price.amount_b_for(1, Rounding::Up).unwrap(), 2);
+        assert_eq!(price.amount_b_for(1, Rounding::Down).unwrap(), 1);
+        assert_eq!(price.amount_a_for(2, Rounding::Down).unwrap(), 1);
+        assert_eq!(price.amount_a_for(2, Rounding::Up).unwrap(), 2);
+    }
+
+    #[test]
+    fn partial_fills_never_undercharge() {
+        // Splitting a fill into pieces can only cost the taker more, never less
+        let (receive, amount) = (1_000_003, 999_983);
+        let price = Price::from_amounts(receive, amount).unwrap();
+        for pieces in [2u64, 3, 7, 10, 1_000] {
+            let chunk = amount / pieces;
+            let mut paid = 0;
+            let mut filled = 0;
+            while filled < amount {
+                let fill = chunk.min(amount - filled).max(1);
+                paid += price.amount_b_for(fill, Rounding::Up).unwrap();
+                filled += fill;
+            }
+            assert!(paid >= receive, "{pieces} pieces paid {paid} < {receive}");
+            assert!(paid - receive <= pieces + 1);
+        }
+    }
+
+    #[test]
+    fn zero_amount_costs_nothing() {
+        let price = Price::from_amounts(7, 3).unwrap();
+        assert_eq!(price.amount_b_for(0, Rounding::Up).unwrap(), 0);
+        assert_eq!(price.amount_a_for(0, Rounding::Down).unwrap(), 0);
+    }
+
+    #[test]
+    fn result_overflow() {
+        // Result larger than u64
+        let price = Price::from_amounts(u64::MAX, 1).unwrap();
+        assert!(is_overflow(price.amount_b_for(2, Rounding::Up)));
+        assert_eq!(price.amount_b_for(1, Rounding::Up).unwrap(), u64::MAX);
+
+        // Intermediate product larger than u128
+        let price = Price::new(u128::MAX / 2, 3).unwrap();
+        assert!(is_overflow(price.amount_b_for(u64::MAX, Rounding::Up)));
+    }
+
+    #[test]
+    fn common_factors_cancel_before_multiplying() {
+        // 2^63 * (2^80 + 1) overflows u128, but 2^63 cancels against the
+        // 2^90 denominator first
+        let price = Price::new((1 << 80) + 1, 1 << 90).unwrap();
+        assert_eq!(price.amount_b_for(1 << 63, Rounding::Down).unwrap(), 1 << 53);
+        assert_eq!(price.amount_b_for(1 << 63, Rounding::Up).unwrap(), (1 << 53) + 1);
+
+        // Without a shared factor the same magnitude does overflow
+        assert!(is_overflow(price.amount_b_for(u64::MAX, Rounding::Up)));
+
+        // Construction reduces by the gcd
+        let price = Price::new(1 << 100, 1 << 62).unwrap();
+        assert_eq!(price, Price { numerator: 1 << 38, denominator: 1 });
+    }
+
+    #[test]
+    fn zero_price_is_rejected() {
+        // The all-zero default is what oracle escrows store; it must never
+        // silently price a take at 0.
+        let price = Price::default();
+        assert_eq!(
+            price.amount_b_for(1, Rounding::Up).unwrap_err(),
+            EscrowError::InvalidPrice.into()
+        );
+    }
+}
@@ -1,12 +1,422 @@
-use anchor_lang::prelude::*;
- 
+use crate::oracle::{OracleConfig, PriceFeed};
+use crate::{errors::EscrowError, price::Price};
+use anchor_lang::{
+  prelude::*,
+  system_program::{transfer, Transfer},
+};
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EscrowKind {
+  /// mint_a is deposited into the escrow-owned `vault` at make time.
+  Vault,
+  /// mint_a stays in `maker_ata_a`; the escrow PDA is approved as its delegate.
+  Delegated,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EscrowStatus {
+  /// Made, nothing taken yet.
+  Open,
+  /// At least one take, with part of `deposited` still on offer.
+  PartiallyFilled,
+  /// All of `deposited` delivered; only donated excess can keep it open.
+  Filled,
+  /// Refunded or cancelled by the maker.
+  Cancelled,
+}
+
+impl EscrowStatus {
+  /// Filled and cancelled escrows are final; everything else may fill
+  /// further or be cancelled.
+  pub fn check_transition(self, next: EscrowStatus) -> Result<()> {
+    match (self, next) {
+      (
+        EscrowStatus::Open | EscrowStatus::PartiallyFilled,
+        EscrowStatus::PartiallyFilled | EscrowStatus::Filled | EscrowStatus::Cancelled,
+      ) => Ok(()),
+      (EscrowStatus::Filled, _) => err!(EscrowError::EscrowAlreadyFilled),
+      (EscrowStatus::Cancelled, _) => err!(EscrowError::EscrowCancelled),
+      _ => err!(EscrowError::InvalidStatusTransition),
+    }
+  }
+}
+
 #[derive(InitSpace)]
 #[account(discriminator = 1)]
   pub struct Escrow {
+  /// Layout version, kept first so later migrations can dispatch on it.
+  pub version: u8,
   pub seed: u64,
   pub maker: Pubkey,
   pub mint_a: Pubkey,
   pub mint_b: Pubkey,
   pub receive: u64,
   pub bump: u8,
-}
\ No newline at end of file
+  pub kind: EscrowKind,
+  /// mint_a committed to the order: what landed in the vault at make time,
+  /// or the delegate allowance for delegated escrows.
+  pub deposited: u64,
+  /// Portion of `deposited` already delivered to takers.
+  pub filled: u64,
+  /// Set for escrows whose mint_b price comes from an oracle at take time;
+  /// `receive` is unused for those.
+  pub oracle: Option<OracleConfig>,
+  /// Rate `receive` was derived from; zero for oracle escrows.
+  pub price: Price,
+  pub status: EscrowStatus,
+  /// Number of takes so far, full or partial.
+  pub fill_count: u32,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+impl Escrow {
+  /// Bump together with any layout change and teach `migrate_escrow` the
+  /// previous one.
+  pub const VERSION: u8 = 1;
+  /// Account size without extensions. The discriminator is a single byte,
+  /// not Anchor's default eight.
+  pub const LEN: usize = Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE;
+
+  /// mint_a still on offer. The vault may hold more than this if someone
+  /// donated to it; that excess belongs to the maker, not the next taker.
+  pub fn remaining(&self) -> u64 {
+    self.deposited - self.filled
+  }
+
+  /// mint_a released for one NFT sold into a collection offer, where
+  /// `receive` counts the NFTs bid for and each fill delivers one: an even
+  /// share of what remains, so the last item also takes any remainder.
+  pub fn collection_item_amount(&self) -> Result<u64> {
+    let items_left = self
+      .receive
+      .checked_sub(self.fill_count as u64)
+      .filter(|items| *items > 0)
+      .ok_or(EscrowError::InvalidAmount)?;
+    Ok(self.remaining() / items_left)
+  }
+
+  /// Moves to `next` if allowed and stamps `updated_at`.
+  pub fn transition(&mut self, next: EscrowStatus, now: i64) -> Result<()> {
+    self.status.check_transition(next)?;
+    self.status = next;
+    self.updated_at = now;
+    Ok(())
+  }
+
+  /// Books a take of `amount` mint_a. The status check comes first so a
+  /// take on a filled escrow reports that rather than a bad amount.
+  pub fn record_fill(&mut self, amount: u64, now: i64) -> Result<()> {
+    let next = if amount == self.remaining() {
+      EscrowStatus::Filled
+    } else {
+      EscrowStatus::PartiallyFilled
+    };
+    self.status.check_transition(next)?;
+    require!(
+      amount > 0 && amount <= self.remaining(),
+      EscrowError::InvalidAmount
+    );
+
+    self.filled += amount;
+    self.fill_count = self
+      .fill_count
+      .checked_add(1)
+      .ok_or(EscrowError::MathOverflow)?;
+    self.transition(next, now)
+  }
+
+  /// Rate a take pays right now: the stored `price`, or for oracle escrows
+  /// the validated feed price with the maker's spread.
+  pub fn current_price(
+    &self,
+    price_feed: Option<&AccountInfo>,
+    decimals_a: u8,
+    decimals_b: u8,
+  ) -> Result<Price> {
+    let Some(oracle) = self.oracle else {
+      return Ok(self.price);
+    };
+    let price_feed = price_feed.ok_or(EscrowError::InvalidPriceFeed)?;
+    let feed = PriceFeed::load(&oracle, price_feed)?;
+    feed.validate(&oracle, Clock::get()?.unix_timestamp)?;
+    feed.price(&oracle, decimals_a, decimals_b)
+  }
+}
+
+/// Zero-copy counterpart of a plain vault `Escrow`, read in place through
+/// `AccountLoader` rather than Borsh-decoded on every instruction. It only
+/// supports full takes at `receive`: no oracle, price ratio or extensions.
+#[account(zero_copy, discriminator = 3)]
+pub struct EscrowZeroCopy {
+  pub seed: u64,
+  pub receive: u64,
+  pub deposited: u64,
+  pub filled: u64,
+  pub created_at: i64,
+  pub maker: Pubkey,
+  pub mint_a: Pubkey,
+  pub mint_b: Pubkey,
+  pub bump: u8,
+  pub version: u8,
+  pub _padding: [u8; 6],
+}
+
+impl EscrowZeroCopy {
+  pub const VERSION: u8 = 1;
+  pub const LEN: usize =
+    EscrowZeroCopy::DISCRIMINATOR.len() + std::mem::size_of::<EscrowZeroCopy>();
+
+  pub fn remaining(&self) -> u64 {
+    self.deposited - self.filled
+  }
+}
+
+/// Resizes a program account to `new_len`, topping up rent from `payer`
+/// when it grows. Shrinking leaves the surplus for whoever closes it.
+pub(crate) fn realloc_account<'info>(
+  account: &AccountInfo<'info>,
+  payer: &AccountInfo<'info>,
+  system_program: &AccountInfo<'info>,
+  new_len: usize,
+) -> Result<()> {
+  let rent = Rent::get()?.minimum_balance(new_len);
+  let shortfall = rent.saturating_sub(account.lamports());
+  if shortfall > 0 {
+    transfer(
+      CpiContext::new(
+        system_program.clone(),
+        Transfer {
+          from: payer.clone(),
+          to: account.clone(),
+        },
+      ),
+      shortfall,
+    )?;
+  }
+  account.realloc(new_len, true)?;
+  Ok(())
+}
+
+/// Replay guard for an off-chain signed order, one per `(maker, nonce)`.
+#[derive(InitSpace)]
+#[account(discriminator = 2)]
+pub struct OrderNonce {
+  pub maker: Pubkey,
+  pub nonce: u64,
+  pub used: bool,
+  pub bump: u8,
+}
+
+/// Per-maker bookkeeping shared by every escrow: the seed `make_next`
+/// hands out and how many escrows are open, capped at `MAX_OPEN_ESCROWS`.
+#[derive(InitSpace)]
+#[account(discriminator = 4)]
+pub struct MakerState {
+  pub maker: Pubkey,
+  pub next_seed: u64,
+  pub open_escrows: u32,
+  pub bump: u8,
+}
+
+impl MakerState {
+  pub const MAX_OPEN_ESCROWS: u32 = 64;
+
+  /// Fills in a freshly `init_if_needed` account; no-op once set up.
+  pub fn init_if_new(&mut self, maker: Pubkey, bump: u8) {
+    if self.maker == Pubkey::default() {
+      self.maker = maker;
+      self.bump = bump;
+    }
+  }
+
+  pub fn open_escrow(&mut self) -> Result<()> {
+    require_gt!(
+      Self::MAX_OPEN_ESCROWS,
+      self.open_escrows,
+      EscrowError::TooManyOpenEscrows
+    );
+    self.open_escrows += 1;
+    Ok(())
+  }
+
+  /// Moves `next_seed` past an explicitly chosen `seed`, so `make_next`
+  /// never lands on an escrow address that is already taken.
+  pub fn claim_seed(&mut self, seed: u64) {
+    if seed >= self.next_seed {
+      self.next_seed = seed.saturating_add(1);
+    }
+  }
+
+  /// Saturates so escrows opened before the counter existed cannot
+  /// underflow it.
+  pub fn close_escrow(&mut self) {
+    self.open_escrows = self.open_escrows.saturating_sub(1);
+  }
+}
+
+/// Open escrows for one mint pair (`[b"pair_registry", mint_a, mint_b]`) or
+/// one maker (`[b"maker_registry", maker]`), so clients can list offers by
+/// reading a PDA instead of scanning with `getProgramAccounts`. Order is not
+/// stable: removals swap the last entry into the gap.
+///
+/// A registry is an index, not a gate: once it holds `MAX_ENTRIES`, further
+/// escrows still open but go unlisted until entries are removed, so a flood
+/// of dust escrows cannot block makes for a pair. Clients that find a full
+/// registry should fall back to `getProgramAccounts`.
+#[account(discriminator = 5)]
+pub struct Registry {
+  pub bump: u8,
+  pub escrows: Vec<Pubkey>,
+}
+
+impl Registry {
+  /// Entries added per resize, so not every insert pays for a realloc.
+  pub const GROWTH: usize = 8;
+  /// Keeps a full registry small enough to deserialize in one instruction.
+  pub const MAX_ENTRIES: usize = 256;
+
+  pub const fn space(capacity: usize) -> usize {
+    Registry::DISCRIMINATOR.len() + 1 + 4 + 32 * capacity
+  }
+
+  /// Fills in a freshly `init_if_needed` account; no-op once set up.
+  pub fn init_if_new(&mut self, bump: u8) {
+    if self.bump == 0 {
+      self.bump = bump;
+    }
+  }
+
+  /// Appends `escrow`, growing the account by `GROWTH` entries when full.
+  /// Skips the listing once the registry holds `MAX_ENTRIES`.
+  pub fn insert<'info>(
+    registry: &mut Account<'info, Registry>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    escrow: Pubkey,
+  ) -> Result<()> {
+    let len = registry.escrows.len();
+    if len >= Self::MAX_ENTRIES {
+      return Ok(());
+    }
+
+    let info = registry.to_account_info();
+    if info.data_len() < Self::space(len + 1) {
+      let capacity = (len + Self::GROWTH).min(Self::MAX_ENTRIES);
+      realloc_account(&info, payer, system_program, Self::space(capacity))?;
+    }
+    registry.escrows.push(escrow);
+    Ok(())
+  }
+
+  /// Drops `escrow` if listed; escrows made before the registry existed are not.
+  pub fn remove(&mut self, escrow: &Pubkey) {
+    if let Some(i) = self.escrows.iter().position(|e| e == escrow) {
+      self.escrows.swap_remove(i);
+    }
+  }
+}
+
+/// Program-wide admin switches at `[b"config"]`. Until `initialize_config`
+/// has run nothing is paused, so existing deployments keep working.
+#[derive(InitSpace, Default)]
+#[account(discriminator = 6)]
+pub struct Config {
+  pub admin: Pubkey,
+  /// Set by `propose_admin`; only takes over once it signs `accept_admin`.
+  pub pending_admin: Option<Pubkey>,
+  pub make_paused: bool,
+  pub take_paused: bool,
+  /// When set, only mints with an `Allowed` listing can be escrowed.
+  /// Denied mints are rejected either way.
+  pub allowlist_only: bool,
+  /// Most a take may pay its referrer, in bps of the maker's payment.
+  /// 0 turns referral fees off.
+  pub max_referral_bps: u16,
+  pub bump: u8,
+}
+
+impl Config {
+  /// Reads the config PDA, treating one that was never initialized as
+  /// all-unpaused. Callers must pin the address with `seeds = [b"config"]`.
+  pub fn load(info: &AccountInfo) -> Result<Config> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+      return Ok(Config::default());
+    }
+    Config::try_deserialize(&mut &info.try_borrow_data()?[..])
+  }
+
+  /// Fee owed to a take's referrer at `bps` of `payment`, rounded down.
+  pub fn referral_fee(&self, payment: u64, bps: u16) -> Result<u64> {
+    require_gte!(self.max_referral_bps, bps, EscrowError::ReferralFeeTooHigh);
+    Ok((payment as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64)
+  }
+
+  /// Whether makes may use the mint whose `[b"mint_listing", mint]` PDA is
+  /// `listing`.
+  pub fn allows_mint(&self, listing: &AccountInfo) -> Result<bool> {
+    let status = MintListing::load(listing)?.map(|l| l.status);
+    Ok(match status {
+      Some(MintStatus::Denied) => false,
+      Some(MintStatus::Allowed) => true,
+      None => !self.allowlist_only,
+    })
+  }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MintStatus {
+  Allowed,
+  Denied,
+}
+
+/// Admin verdict on one mint, at `[b"mint_listing", mint]`. Mints without a
+/// listing are allowed unless `Config::allowlist_only` is set.
+#[derive(InitSpace)]
+#[account(discriminator = 7)]
+pub struct MintListing {
+  pub mint: Pubkey,
+  pub status: MintStatus,
+  pub bump: u8,
+}
+
+impl MintListing {
+  /// Reads a listing PDA, or `None` if the mint was never listed. Callers
+  /// must pin the address with its seeds.
+  pub fn load(info: &AccountInfo) -> Result<Option<MintListing>> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+      return Ok(None);
+    }
+    MintListing::try_deserialize(&mut &info.try_borrow_data()?[..]).map(Some)
+  }
+}
+
+/// mint_a one taker has filled from one allowlisted escrow, at
+/// `[b"taker_fill", escrow, taker]`, to hold them to their allowlist cap.
+#[derive(InitSpace)]
+#[account(discriminator = 8)]
+pub struct TakerFill {
+  /// `created_at` of the escrow being counted, so an escrow remade at the
+  /// same address starts from zero.
+  pub escrow_created_at: i64,
+  pub filled: u64,
+}
+
+impl TakerFill {
+  /// Books a take of `amount` from `escrow`, up to `cap` in total.
+  pub fn record(&mut self, escrow: &Escrow, amount: u64, cap: u64) -> Result<()> {
+    if self.escrow_created_at != escrow.created_at {
+      self.escrow_created_at = escrow.created_at;
+      self.filled = 0;
+    }
+    let filled = self
+      .filled
+      .checked_add(amount)
+      .ok_or(EscrowError::MathOverflow)?;
+    require_gte!(cap, filled, EscrowError::TakerCapExceeded);
+    self.filled = filled;
+    Ok(())
+  }
+}
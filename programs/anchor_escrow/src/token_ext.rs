@@ -0,0 +1,216 @@
+use crate::errors::EscrowError;
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        default_account_state::DefaultAccountState, interest_bearing_mint::InterestBearingConfig,
+        non_transferable::NonTransferable, permanent_delegate::PermanentDelegate,
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::AccountState,
+};
+
+/// Mint features that let someone other than the escrow move, freeze or
+/// strand its tokens. Makes refuse them unless set in `accepted_hazards`.
+pub mod hazard {
+    /// The delegate can transfer or burn out of any account, vault included.
+    pub const PERMANENT_DELEGATE: u8 = 1 << 0;
+    /// Deposited tokens can never leave the vault.
+    pub const NON_TRANSFERABLE: u8 = 1 << 1;
+    /// New accounts, such as the vault, start frozen.
+    pub const DEFAULT_FROZEN: u8 = 1 << 2;
+    /// The freeze authority can freeze the vault or the maker's account.
+    pub const FREEZE_AUTHORITY: u8 = 1 << 3;
+}
+
+/// Token-2022 transfer fee withheld when `amount` of `mint` moves this
+/// epoch. Legacy SPL mints and mints without a `TransferFeeConfig` charge 0.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(0);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+    config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(error!(EscrowError::MathOverflow))
+}
+
+/// Fails with the first hazard `mint` has that `accepted` does not include.
+pub fn check_mint_hazards(mint: &AccountInfo, accepted: u8) -> Result<()> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let refuse = |hazard: u8, error: EscrowError| -> Result<()> {
+        if accepted & hazard == 0 {
+            return Err(error.into());
+        }
+        Ok(())
+    };
+
+    if state.base.freeze_authority.is_some() {
+        refuse(
+            hazard::FREEZE_AUTHORITY,
+            EscrowError::MintHasFreezeAuthority,
+        )?;
+    }
+    if let Ok(delegate) = state.get_extension::<PermanentDelegate>() {
+        if Option::<Pubkey>::from(delegate.delegate).is_some() {
+            refuse(
+                hazard::PERMANENT_DELEGATE,
+                EscrowError::MintHasPermanentDelegate,
+            )?;
+        }
+    }
+    if state.get_extension::<NonTransferable>().is_ok() {
+        refuse(hazard::NON_TRANSFERABLE, EscrowError::MintNonTransferable)?;
+    }
+    if let Ok(default_state) = state.get_extension::<DefaultAccountState>() {
+        if default_state.state == AccountState::Frozen as u8 {
+            refuse(hazard::DEFAULT_FROZEN, EscrowError::MintDefaultFrozen)?;
+        }
+    }
+    Ok(())
+}
+
+const SECONDS_PER_YEAR: f64 = 60. * 60. * 24. * 365.24;
+const ONE_IN_BASIS_POINTS: f64 = 10_000.;
+/// Token-2022 added `ScaledUiAmount` after the version anchor-spl pins, so
+/// its TLV entry is read by hand.
+const SCALED_UI_AMOUNT: u16 = 25;
+/// Where a mint's TLV entries start: the base mint padded to a token
+/// account's length, then the account type byte.
+const MINT_TLV_OFFSET: usize = 166;
+
+/// Factor a Token-2022 mint applies to raw amounts for display at
+/// `unix_timestamp`: interest accrued under `InterestBearingConfig`
+/// (continuously compounded, as Token-2022 computes it), the multiplier in
+/// effect for `ScaledUiAmount`, and 1 for every other mint.
+pub fn ui_multiplier(mint: &AccountInfo, unix_timestamp: i64) -> Result<f64> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(1.);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    if let Ok(config) = state.get_extension::<InterestBearingConfig>() {
+        return Ok(interest_multiplier(config, unix_timestamp));
+    }
+    if let Some(config) = tlv_value(&data, SCALED_UI_AMOUNT) {
+        return scaled_multiplier(config, unix_timestamp);
+    }
+    Ok(1.)
+}
+
+fn interest_multiplier(config: &InterestBearingConfig, unix_timestamp: i64) -> f64 {
+    let initialized = i64::from(config.initialization_timestamp);
+    let updated = i64::from(config.last_update_timestamp);
+    let growth = |rate: i16, seconds: i64| {
+        (rate as f64 * seconds as f64 / SECONDS_PER_YEAR / ONE_IN_BASIS_POINTS).exp()
+    };
+    growth(config.pre_update_average_rate.into(), updated - initialized)
+        * growth(config.current_rate.into(), unix_timestamp - updated)
+}
+
+/// `ScaledUiAmountConfig`: authority (32), multiplier (f64), the timestamp
+/// a new multiplier takes over (i64), and that new multiplier (f64).
+fn scaled_multiplier(config: &[u8], unix_timestamp: i64) -> Result<f64> {
+    let field = |at: usize| -> Result<[u8; 8]> {
+        config
+            .get(at..at + 8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(error!(EscrowError::InvalidUiAmountConfig))
+    };
+    let multiplier = f64::from_le_bytes(field(32)?);
+    let new_multiplier_from = i64::from_le_bytes(field(40)?);
+    let new_multiplier = f64::from_le_bytes(field(48)?);
+    Ok(if unix_timestamp >= new_multiplier_from {
+        new_multiplier
+    } else {
+        multiplier
+    })
+}
+
+fn tlv_value(mint_data: &[u8], extension_type: u16) -> Option<&[u8]> {
+    let mut at = MINT_TLV_OFFSET;
+    while let Some(header) = mint_data.get(at..at + 4) {
+        let ty = u16::from_le_bytes([header[0], header[1]]);
+        let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if ty == 0 {
+            return None;
+        }
+        if ty == extension_type {
+            return mint_data.get(at + 4..at + 4 + len);
+        }
+        at += 4 + len;
+    }
+    None
+}
+
+/// The display value of a raw `amount`, in UI units times 10^decimals, so
+/// it equals `amount` when `multiplier` is 1. Rounds to nearest.
+pub fn raw_to_ui(amount: u64, multiplier: f64) -> Result<u64> {
+    to_u64(amount as f64 * multiplier)
+}
+
+/// The raw amount that displays as `ui_amount` (UI units times
+/// 10^decimals). Rounds to nearest, as Token-2022's UI conversion does.
+pub fn ui_to_raw(ui_amount: u64, multiplier: f64) -> Result<u64> {
+    require!(multiplier > 0., EscrowError::InvalidUiAmountConfig);
+    to_u64(ui_amount as f64 / multiplier)
+}
+
+fn to_u64(value: f64) -> Result<u64> {
+    let rounded = value.round();
+    require!(
+        rounded.is_finite() && rounded >= 0. && rounded < u64::MAX as f64,
+        EscrowError::MathOverflow
+    );
+    Ok(rounded as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scaled_config(multiplier: f64, new_from: i64, new_multiplier: f64) -> Vec<u8> {
+        let mut config = vec![0u8; 32];
+        config.extend(multiplier.to_le_bytes());
+        config.extend(new_from.to_le_bytes());
+        config.extend(new_multiplier.to_le_bytes());
+        config
+    }
+
+    #[test]
+    fn ui_round_trip() {
+        assert_eq!(raw_to_ui(1_000_000, 1.05).unwrap(), 1_050_000);
+        assert_eq!(ui_to_raw(1_050_000, 1.05).unwrap(), 1_000_000);
+        assert_eq!(ui_to_raw(7, 1.).unwrap(), 7);
+        assert_eq!(
+            ui_to_raw(u64::MAX, 0.5).unwrap_err(),
+            EscrowError::MathOverflow.into()
+        );
+    }
+
+    #[test]
+    fn scaled_multiplier_switches_at_timestamp() {
+        let config = scaled_config(2., 1_000, 3.);
+        assert_eq!(scaled_multiplier(&config, 999).unwrap(), 2.);
+        assert_eq!(scaled_multiplier(&config, 1_000).unwrap(), 3.);
+    }
+
+    #[test]
+    fn tlv_value_finds_entry_after_others() {
+        let mut data = vec![0u8; MINT_TLV_OFFSET];
+        data.extend(1u16.to_le_bytes());
+        data.extend(2u16.to_le_bytes());
+        data.extend([9, 9]);
+        data.extend(SCALED_UI_AMOUNT.to_le_bytes());
+        data.extend(3u16.to_le_bytes());
+        data.extend([1, 2, 3]);
+
+        assert_eq!(tlv_value(&data, SCALED_UI_AMOUNT), Some(&[1u8, 2, 3][..]));
+        assert_eq!(tlv_value(&data, 10), None);
+    }
+}
@@ -0,0 +1,51 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("6r2E4hFibeJbkFagfqJMh7vvqhj45edf3HKTLpen2P2s");
+
+/// Leading magic of a price account, as in Pyth's legacy layout.
+pub const MAGIC: u32 = 0xa1b2c3d4;
+pub const VERSION: u32 = 2;
+/// Pyth account type tag for a price account.
+pub const ACCOUNT_TYPE_PRICE: u32 = 3;
+/// 240 bytes of header and aggregate, then 32 publisher components of 96
+pub const PRICE_ACCOUNT_LEN: usize = 240 + 32 * 96;
+
+/// Test-only oracle: anyone may overwrite a price account owned by this
+/// program. Accounts are raw (no Anchor discriminator) and laid out like a
+/// Pyth legacy price account; only the fields the escrow reads are written.
+#[program]
+pub mod mock_oracle {
+    use super::*;
+    pub fn set_price(
+        ctx: Context<SetPrice>,
+        price: i64,
+        conf: u64,
+        expo: i32,
+        publish_time: i64,
+        status: u32,
+    ) -> Result<()> {
+        let mut data = ctx.accounts.price_feed.try_borrow_mut_data()?;
+        require_gte!(data.len(), PRICE_ACCOUNT_LEN, ErrorCode::AccountDidNotDeserialize);
+
+        data[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&VERSION.to_le_bytes());
+        data[8..12].copy_from_slice(&ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[12..16].copy_from_slice(&(PRICE_ACCOUNT_LEN as u32).to_le_bytes());
+        data[20..24].copy_from_slice(&expo.to_le_bytes());
+        data[96..104].copy_from_slice(&publish_time.to_le_bytes());
+        // Aggregate price info
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[216..224].copy_from_slice(&conf.to_le_bytes());
+        data[224..228].copy_from_slice(&status.to_le_bytes());
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    /// CHECK: raw price account, created by the client with this program as owner
+    #[account(mut, owner = crate::ID)]
+    pub price_feed: UncheckedAccount<'info>,
+}
@@ -0,0 +1,16 @@
+#!/usr/bin/env bash
+# Builds the original release of the escrow program, whose `Escrow` layout
+# `EscrowV0` in migrate_escrow.rs mirrors, and copies it to
+# tests/fixtures/anchor_escrow_v0.so for tests/upgrade-tests.rs.
+set -euo pipefail
+
+baseline=ec5130d
+root=$(git rev-parse --show-toplevel)
+worktree=$(mktemp -d)
+trap 'git -C "$root" worktree remove --force "$worktree"' EXIT
+
+git -C "$root" worktree add --detach "$worktree" "$baseline"
+(cd "$worktree" && anchor build -p anchor_escrow)
+
+mkdir -p "$root/tests/fixtures"
+cp "$worktree/target/deploy/anchor_escrow.so" "$root/tests/fixtures/anchor_escrow_v0.so"
@@ -0,0 +1,143 @@
+use litesvm_token::spl_token;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    hash::hashv,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+use anchor_escrow::types::{AllowlistProof, EscrowExtension, MerkleAllowlist};
+
+mod common;
+
+/// What the taker may fill under the allowlist in `setup`.
+const CAP: u64 = 400;
+
+fn leaf(taker: &Pubkey, cap: Option<u64>) -> [u8; 32] {
+    match cap {
+        Some(cap) => hashv(&[&[0], taker.as_ref(), &cap.to_le_bytes()]),
+        None => hashv(&[&[0], taker.as_ref()]),
+    }
+    .to_bytes()
+}
+
+fn node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[&[1], &lo, &hi]).to_bytes()
+}
+
+const SEED: u64 = 10;
+
+/// An escrow of 1_000 mint_a for 1_000 mint_b, open to the taker (capped)
+/// and to the second keypair returned (uncapped). The third is not listed.
+fn setup() -> (common::Env, Keypair, Keypair) {
+    let mut env = common::setup(1_000, 1_000);
+    let other = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let stranger = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    for who in [&other, &stranger] {
+        let ata_b = env.ctx.svm.create_associated_token_account(&env.mint_b, who).unwrap();
+        env.ctx.svm.mint_to(&env.mint_b, &ata_b, &env.maker, 1_000).unwrap();
+    }
+
+    let root = node(leaf(&env.taker.pubkey(), Some(CAP)), leaf(&other.pubkey(), None));
+    env.make(SEED, 1_000, 1_000, vec![EscrowExtension::MerkleAllowlist(MerkleAllowlist { root })])
+        .assert_success();
+    (env, other, stranger)
+}
+
+fn taker_fill(env: &common::Env, taker: &Pubkey) -> Pubkey {
+    env.ctx.svm.get_pda(
+        &[b"taker_fill", env.escrow(SEED).as_ref(), taker.as_ref()],
+        &anchor_escrow::ID,
+    )
+}
+
+/// A full take, or a partial one of `amount`.
+fn take(
+    env: &mut common::Env,
+    taker: &Keypair,
+    amount: Option<u64>,
+    allowlist: Option<AllowlistProof>,
+    with_taker_fill: bool,
+) -> litesvm_utils::TransactionResult {
+    let accounts = anchor_escrow::client::accounts::Take {
+        taker_fill: with_taker_fill.then(|| taker_fill(env, &taker.pubkey())),
+        ..common::take_accounts(
+            &taker.pubkey(),
+            &env.maker.pubkey(),
+            &env.escrow(SEED),
+            &env.mint_a,
+            &env.mint_b,
+            &spl_token::id(),
+        )
+    };
+    match amount {
+        Some(amount) => env.execute(
+            accounts,
+            anchor_escrow::client::args::TakePartial { amount, referral_bps: 0, allowlist },
+            taker,
+        ),
+        None => env.execute(
+            accounts,
+            anchor_escrow::client::args::Take { referral_bps: 0, allowlist },
+            taker,
+        ),
+    }
+}
+
+/// The taker's proof: the uncapped `other` leaf is its sibling.
+fn taker_proof(other: &Keypair, cap: u64) -> Option<AllowlistProof> {
+    Some(AllowlistProof { proof: vec![leaf(&other.pubkey(), None)], cap: Some(cap) })
+}
+
+fn other_proof(env: &common::Env) -> Option<AllowlistProof> {
+    Some(AllowlistProof { proof: vec![leaf(&env.taker.pubkey(), Some(CAP))], cap: None })
+}
+
+#[test]
+fn test_allowlisted_taker_fills() {
+    let (mut env, other, _) = setup();
+
+    take(&mut env, &other, None, None, false).assert_anchor_error("NotAllowlisted");
+    let proof = other_proof(&env);
+    take(&mut env, &other, None, proof, false).assert_success();
+    env.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&other.pubkey(), &env.mint_a),
+        1_000,
+    );
+}
+
+#[test]
+fn test_unlisted_taker_is_refused() {
+    let (mut env, other, stranger) = setup();
+
+    // Neither of the listed takers' proofs fits the stranger's key
+    let proof = other_proof(&env);
+    take(&mut env, &stranger, None, proof, false).assert_anchor_error("NotAllowlisted");
+    take(&mut env, &stranger, Some(100), taker_proof(&other, CAP), true).assert_anchor_error("NotAllowlisted");
+}
+
+#[test]
+fn test_capped_taker_is_held_to_cap_across_fills() {
+    let (mut env, other, _) = setup();
+    let taker = env.taker.insecure_clone();
+
+    // The cap is part of the leaf, so the taker cannot claim a higher one
+    take(&mut env, &taker, Some(100), taker_proof(&other, CAP + 1), true).assert_anchor_error("NotAllowlisted");
+    take(&mut env, &taker, Some(100), taker_proof(&other, CAP), false).assert_anchor_error("MissingTakerFill");
+
+    take(&mut env, &taker, Some(300), taker_proof(&other, CAP), true).assert_success();
+    take(&mut env, &taker, Some(101), taker_proof(&other, CAP), true).assert_anchor_error("TakerCapExceeded");
+    take(&mut env, &taker, Some(100), taker_proof(&other, CAP), true).assert_success();
+    env.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&taker.pubkey(), &env.mint_a),
+        CAP,
+    );
+
+    // The rest is still open to the uncapped taker
+    let proof = other_proof(&env);
+    take(&mut env, &other, None, proof, false).assert_success();
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use common::{
+    create_token_2022_ata, create_token_2022_collection, create_token_2022_mint,
+    create_token_2022_nft, mint_token_2022, token_2022_ata,
+};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+struct Env {
+    ctx: AnchorContext,
+    /// Bids mint_a for NFTs from `collection`
+    bidder: Keypair,
+    /// Holds NFTs from `collection`, whose authority it also is
+    seller: Keypair,
+    mint_a: Pubkey,
+    collection: Pubkey,
+}
+
+fn setup() -> Env {
+    let mut ctx = common::load_program();
+
+    let bidder = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let seller = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = create_token_2022_mint(&mut ctx.svm, &bidder, 6, &[], |_| vec![], None);
+    let bidder_ata_a = create_token_2022_ata(&mut ctx.svm, &bidder, &bidder.pubkey(), &mint_a);
+    mint_token_2022(&mut ctx.svm, &bidder, &mint_a, &bidder_ata_a, 10_000);
+
+    let collection = create_token_2022_collection(&mut ctx.svm, &seller);
+
+    Env { ctx, bidder, seller, mint_a, collection }
+}
+
+impl Env {
+    fn escrow(&self, seed: u64) -> Pubkey {
+        common::escrow(&self.bidder.pubkey(), seed)
+    }
+
+    fn make_accounts(&self, seed: u64, mint_b: Pubkey) -> anchor_escrow::client::accounts::Make {
+        common::make_accounts(&self.bidder.pubkey(), seed, &self.mint_a, &mint_b, &spl_token_2022::id())
+    }
+
+    fn bid(&mut self, seed: u64, price: u64, quantity: u64) -> litesvm_utils::TransactionResult {
+        let bidder = self.bidder.insecure_clone();
+        let ix = self.ctx.program()
+            .accounts(self.make_accounts(seed, self.collection))
+            .args(anchor_escrow::client::args::MakeCollectionBid {
+                seed,
+                price,
+                quantity,
+                accepted_hazards: 0,
+            })
+            .instruction()
+            .unwrap();
+        self.ctx.execute_instruction(ix, &[&bidder]).unwrap()
+    }
+
+    fn take_accounts(&self, seed: u64, registered_mint_b: Pubkey, nft: Pubkey) -> anchor_escrow::client::accounts::Take {
+        anchor_escrow::client::accounts::Take {
+            pair_registry: common::pair_registry(&self.mint_a, &registered_mint_b),
+            ..common::take_accounts(
+                &self.seller.pubkey(),
+                &self.bidder.pubkey(),
+                &self.escrow(seed),
+                &self.mint_a,
+                &nft,
+                &spl_token_2022::id(),
+            )
+        }
+    }
+
+    fn sell(&mut self, seed: u64, nft: Pubkey) -> litesvm_utils::TransactionResult {
+        let seller = self.seller.insecure_clone();
+        let ix = self.ctx.program()
+            .accounts(self.take_accounts(seed, self.collection, nft))
+            .args(anchor_escrow::client::args::SellIntoBid {})
+            .instruction()
+            .unwrap();
+        self.ctx.execute_instruction(ix, &[&seller]).unwrap()
+    }
+
+    fn member(&mut self) -> Pubkey {
+        let seller = self.seller.insecure_clone();
+        let collection = self.collection;
+        create_token_2022_nft(&mut self.ctx.svm, &seller, &[], Some(&collection))
+    }
+}
+
+#[test]
+fn test_one_bid_buys_several_items() {
+    let mut env = setup();
+    env.bid(1, 300, 3).assert_success();
+    let seller_ata_a = token_2022_ata(&env.seller.pubkey(), &env.mint_a);
+
+    for sold in 1..=3 {
+        let nft = env.member();
+        env.sell(1, nft).assert_success();
+
+        env.ctx.svm.assert_token_balance(&seller_ata_a, 300 * sold);
+        env.ctx.svm.assert_token_balance(&token_2022_ata(&env.bidder.pubkey(), &nft), 1);
+        if sold < 3 {
+            let state: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow(1)).unwrap();
+            assert_eq!(state.fill_count, sold as u32);
+        }
+    }
+
+    // Filled after the third item
+    env.ctx.svm.assert_account_closed(&env.escrow(1));
+    let nft = env.member();
+    env.sell(1, nft).assert_failure();
+}
+
+#[test]
+fn test_sell_into_bid_checks_the_item() {
+    let mut env = setup();
+    let seller = env.seller.insecure_clone();
+    env.bid(1, 300, 3).assert_success();
+
+    let other_collection = create_token_2022_collection(&mut env.ctx.svm, &seller);
+    let outsider = create_token_2022_nft(&mut env.ctx.svm, &seller, &[], Some(&other_collection));
+    env.sell(1, outsider).assert_anchor_error("NotInCollection");
+
+    // `take` would ask one NFT for all three items' worth
+    let nft = env.member();
+    let ix = env.ctx.program()
+        .accounts(env.take_accounts(1, env.collection, nft))
+        .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(ix, &[&seller])
+        .unwrap()
+        .assert_anchor_error("InvalidAmount");
+    env.sell(1, nft).assert_success();
+}
+
+#[test]
+fn test_sell_into_bid_needs_a_collection_offer() {
+    let mut env = setup();
+    let bidder = env.bidder.insecure_clone();
+    let nft = env.member();
+
+    // An ordinary escrow that happens to want this exact NFT
+    let ix = env.ctx.program()
+        .accounts(env.make_accounts(2, nft))
+        .args(anchor_escrow::client::args::Make {
+            seed: 2,
+            receive: 1,
+            amount: 300,
+            extensions: vec![],
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(ix, &[&bidder])
+        .unwrap()
+        .assert_success();
+
+    let seller = env.seller.insecure_clone();
+    let ix = env.ctx.program()
+        .accounts(env.take_accounts(2, nft, nft))
+        .args(anchor_escrow::client::args::SellIntoBid {})
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(ix, &[&seller])
+        .unwrap()
+        .assert_anchor_error("NotACollectionOffer");
+}
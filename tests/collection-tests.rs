@@ -0,0 +1,160 @@
+mod common;
+
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use common::{
+    create_token_2022_ata, create_token_2022_collection, create_token_2022_mint,
+    create_token_2022_nft, mint_token_2022, token_2022_ata,
+};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+struct Env {
+    ctx: AnchorContext,
+    maker: Keypair,
+    /// Holds NFTs from `collection`, whose authority it also is
+    taker: Keypair,
+    mint_a: Pubkey,
+    collection: Pubkey,
+}
+
+fn setup() -> Env {
+    let mut ctx = common::load_program();
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = create_token_2022_mint(&mut ctx.svm, &maker, 6, &[], |_| vec![], None);
+    let maker_ata_a = create_token_2022_ata(&mut ctx.svm, &maker, &maker.pubkey(), &mint_a);
+    mint_token_2022(&mut ctx.svm, &maker, &mint_a, &maker_ata_a, 1_000);
+
+    let collection = create_token_2022_collection(&mut ctx.svm, &taker);
+
+    Env { ctx, maker, taker, mint_a, collection }
+}
+
+impl Env {
+    fn escrow(&self, seed: u64) -> Pubkey {
+        common::escrow(&self.maker.pubkey(), seed)
+    }
+
+    fn make_accounts(&self, seed: u64, mint_a: Pubkey, mint_b: Pubkey) -> anchor_escrow::client::accounts::Make {
+        common::make_accounts(&self.maker.pubkey(), seed, &mint_a, &mint_b, &spl_token_2022::id())
+    }
+
+    fn make_collection_offer(&mut self, seed: u64) -> litesvm_utils::TransactionResult {
+        let maker = self.maker.insecure_clone();
+        let ix = self.ctx.program()
+            .accounts(self.make_accounts(seed, self.mint_a, self.collection))
+            .args(anchor_escrow::client::args::MakeCollectionOffer {
+                seed,
+                amount: 1_000,
+                accepted_hazards: 0,
+            })
+            .instruction()
+            .unwrap();
+        self.ctx.execute_instruction(ix, &[&maker]).unwrap()
+    }
+
+    /// Delivers `nft` into the collection offer made with `seed`.
+    fn take(&mut self, seed: u64, nft: Pubkey, amount: Option<u64>) -> litesvm_utils::TransactionResult {
+        let taker = self.taker.insecure_clone();
+        let program = self.ctx.program().accounts(anchor_escrow::client::accounts::Take {
+            pair_registry: common::pair_registry(&self.mint_a, &self.collection),
+            ..common::take_accounts(
+                &taker.pubkey(),
+                &self.maker.pubkey(),
+                &self.escrow(seed),
+                &self.mint_a,
+                &nft,
+                &spl_token_2022::id(),
+            )
+        });
+        let ix = match amount {
+            Some(amount) => program.args(anchor_escrow::client::args::TakePartial { amount, referral_bps: 0, allowlist: None }),
+            None => program.args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None }),
+        }
+        .instruction()
+        .unwrap();
+        self.ctx.execute_instruction(ix, &[&taker]).unwrap()
+    }
+
+    fn taker_nft(&mut self, collection: Option<Pubkey>) -> Pubkey {
+        let taker = self.taker.insecure_clone();
+        create_token_2022_nft(&mut self.ctx.svm, &taker, &[], collection.as_ref())
+    }
+}
+
+#[test]
+fn test_collection_offer_takes_any_member() {
+    let mut env = setup();
+    let _first = env.taker_nft(Some(env.collection));
+    let second = env.taker_nft(Some(env.collection));
+    env.make_collection_offer(1).assert_success();
+
+    // The taker chooses which member to deliver
+    env.take(1, second, None).assert_success();
+
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.maker.pubkey(), &second), 1);
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.taker.pubkey(), &env.mint_a), 1_000);
+    env.ctx.svm.assert_account_closed(&env.escrow(1));
+}
+
+#[test]
+fn test_collection_offer_rejects_outsiders() {
+    let mut env = setup();
+    let taker = env.taker.insecure_clone();
+    env.make_collection_offer(1).assert_success();
+
+    let other_collection = create_token_2022_collection(&mut env.ctx.svm, &taker);
+    let outsider = env.taker_nft(Some(other_collection));
+    env.take(1, outsider, None).assert_anchor_error("NotInCollection");
+
+    let loose = env.taker_nft(None);
+    env.take(1, loose, None).assert_anchor_error("NotInCollection");
+
+    // Whole-number tokens that are not one of a kind
+    let fungible = create_token_2022_mint(&mut env.ctx.svm, &taker, 0, &[], |_| vec![], None);
+    let ata = create_token_2022_ata(&mut env.ctx.svm, &taker, &taker.pubkey(), &fungible);
+    mint_token_2022(&mut env.ctx.svm, &taker, &fungible, &ata, 5);
+    env.take(1, fungible, None).assert_anchor_error("NotAnNft");
+
+    // One NFT buys the whole offer, never part of it
+    let member = env.taker_nft(Some(env.collection));
+    env.take(1, member, Some(500)).assert_anchor_error("InvalidAmount");
+    env.take(1, member, None).assert_success();
+}
+
+#[test]
+fn test_make_nft_checks_collection_membership() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let mint_b = env.mint_a;
+    let collection = create_token_2022_collection(&mut env.ctx.svm, &maker);
+    let nft = create_token_2022_nft(&mut env.ctx.svm, &maker, &[], Some(&collection));
+
+    let make_nft = |env: &mut Env, seed: u64, collection: Pubkey| {
+        let ix = env.ctx.program()
+            .accounts(env.make_accounts(seed, nft, mint_b))
+            .args(anchor_escrow::client::args::MakeNft {
+                seed,
+                receive: 100,
+                collection: Some(collection),
+                accepted_hazards: 0,
+            })
+            .instruction()
+            .unwrap();
+        env.ctx.execute_instruction(ix, &[&maker]).unwrap()
+    };
+
+    let taker_collection = env.collection;
+    make_nft(&mut env, 1, taker_collection).assert_anchor_error("NotInCollection");
+    make_nft(&mut env, 1, collection).assert_success();
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.escrow(1), &nft), 1);
+}
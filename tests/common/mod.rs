@@ -0,0 +1,551 @@
+//! Helpers shared by the integration tests. `litesvm-token` only builds
+//! classic SPL Token mints, so mints with extensions are set up here.
+#![allow(dead_code)]
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_litesvm::{AnchorContext, AnchorLiteSVM};
+use litesvm::LiteSVM;
+use litesvm_token::spl_token;
+use litesvm_utils::{TestHelpers, TransactionResult};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, get_program_data_address, UpgradeableLoaderState},
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account,
+};
+use spl_token_2022::{
+    extension::{group_member_pointer, group_pointer, metadata_pointer, ExtensionType},
+    state::Mint,
+};
+use spl_token_metadata_interface::state::Field;
+
+use crate::anchor_escrow::{
+    client::{accounts, args},
+    types::EscrowExtension,
+};
+
+/// The escrow PDA for `maker`'s `seed`.
+pub fn escrow(maker: &Pubkey, seed: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+        &crate::anchor_escrow::ID,
+    )
+    .0
+}
+
+/// The per-maker `MakerState` PDA that vault escrow instructions require.
+pub fn maker_state(maker: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"maker_state", maker.as_ref()], &crate::anchor_escrow::ID).0
+}
+
+/// Registry of open escrows for one mint pair.
+pub fn pair_registry(mint_a: &Pubkey, mint_b: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"pair_registry", mint_a.as_ref(), mint_b.as_ref()],
+        &crate::anchor_escrow::ID,
+    )
+    .0
+}
+
+/// Registry of one maker's open escrows.
+pub fn maker_registry(maker: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"maker_registry", maker.as_ref()], &crate::anchor_escrow::ID).0
+}
+
+/// The program-wide `Config` PDA checked by make and take instructions.
+pub fn config() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &crate::anchor_escrow::ID).0
+}
+
+/// A mint's allow/deny listing, checked by every make instruction.
+pub fn mint_listing(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"mint_listing", mint.as_ref()], &crate::anchor_escrow::ID).0
+}
+
+/// `Make` accounts for `maker`'s escrow `seed`, deposited from the maker's ATA.
+pub fn make_accounts(
+    maker: &Pubkey,
+    seed: u64,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::Make {
+    let escrow = escrow(maker, seed);
+    accounts::Make {
+        maker: *maker,
+        escrow,
+        mint_a: *mint_a,
+        mint_b: *mint_b,
+        maker_ata_a: get_associated_token_address_with_program_id(maker, mint_a, token_program),
+        vault: get_associated_token_address_with_program_id(&escrow, mint_a, token_program),
+        associated_token_program: spl_associated_token_account::id(),
+        token_program: *token_program,
+        system_program: system_program::id(),
+        maker_state: maker_state(maker),
+        pair_registry: pair_registry(mint_a, mint_b),
+        maker_registry: maker_registry(maker),
+        config: config(),
+        mint_a_listing: mint_listing(mint_a),
+        mint_b_listing: mint_listing(mint_b),
+    }
+}
+
+/// The zero-copy escrow PDA for `maker`'s `seed`.
+pub fn escrow_zero_copy(maker: &Pubkey, seed: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow_zc", maker.as_ref(), &seed.to_le_bytes()],
+        &crate::anchor_escrow::ID,
+    )
+    .0
+}
+
+/// `MakeZeroCopy` accounts: `make_accounts` under the `escrow_zc` seeds.
+pub fn make_zero_copy_accounts(
+    maker: &Pubkey,
+    seed: u64,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::MakeZeroCopy {
+    let make = make_accounts(maker, seed, mint_a, mint_b, token_program);
+    let escrow = escrow_zero_copy(maker, seed);
+    accounts::MakeZeroCopy {
+        maker: make.maker,
+        escrow,
+        mint_a: make.mint_a,
+        mint_b: make.mint_b,
+        maker_ata_a: make.maker_ata_a,
+        vault: get_associated_token_address_with_program_id(&escrow, mint_a, token_program),
+        associated_token_program: make.associated_token_program,
+        token_program: make.token_program,
+        system_program: make.system_program,
+        maker_state: make.maker_state,
+        pair_registry: make.pair_registry,
+        maker_registry: make.maker_registry,
+        config: make.config,
+        mint_a_listing: make.mint_a_listing,
+        mint_b_listing: make.mint_b_listing,
+    }
+}
+
+/// `MakeDelegated` accounts: `make_accounts` without the vault.
+pub fn make_delegated_accounts(
+    maker: &Pubkey,
+    seed: u64,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::MakeDelegated {
+    let make = make_accounts(maker, seed, mint_a, mint_b, token_program);
+    accounts::MakeDelegated {
+        maker: make.maker,
+        escrow: make.escrow,
+        mint_a: make.mint_a,
+        mint_b: make.mint_b,
+        maker_ata_a: make.maker_ata_a,
+        token_program: make.token_program,
+        system_program: make.system_program,
+        maker_state: make.maker_state,
+        pair_registry: make.pair_registry,
+        maker_registry: make.maker_registry,
+        config: make.config,
+        mint_a_listing: make.mint_a_listing,
+        mint_b_listing: make.mint_b_listing,
+    }
+}
+
+/// `Take` accounts for `taker` filling `escrow` between the two parties'
+/// ATAs, with every optional account left out. Tests override the fields
+/// they exercise with struct update syntax.
+pub fn take_accounts(
+    taker: &Pubkey,
+    maker: &Pubkey,
+    escrow: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::Take {
+    accounts::Take {
+        taker: *taker,
+        maker: *maker,
+        escrow: *escrow,
+        mint_a: *mint_a,
+        mint_b: *mint_b,
+        vault: get_associated_token_address_with_program_id(escrow, mint_a, token_program),
+        taker_ata_a: Some(get_associated_token_address_with_program_id(taker, mint_a, token_program)),
+        taker_ata_b: get_associated_token_address_with_program_id(taker, mint_b, token_program),
+        maker_ata_b: Some(get_associated_token_address_with_program_id(maker, mint_b, token_program)),
+        associated_token_program: spl_associated_token_account::id(),
+        token_program: *token_program,
+        system_program: system_program::id(),
+        price_feed: None,
+        maker_state: maker_state(maker),
+        pair_registry: pair_registry(mint_a, mint_b),
+        maker_registry: maker_registry(maker),
+        config: config(),
+        memo_program: None,
+        maker_destination_b: None,
+        taker_destination_a: None,
+        referrer_b: None,
+        taker_gate: None,
+        taker_fill: None,
+    }
+}
+
+/// The program loaded into a fresh LiteSVM.
+pub fn load_program() -> AnchorContext {
+    AnchorLiteSVM::build_with_program(
+        crate::anchor_escrow::ID,
+        include_bytes!("../../target/deploy/anchor_escrow.so"),
+    )
+}
+
+/// The fixture most suites start from: a funded maker and taker trading two
+/// mints of `token_program`.
+pub struct Env {
+    pub ctx: AnchorContext,
+    pub maker: Keypair,
+    pub taker: Keypair,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub token_program: Pubkey,
+}
+
+/// Loads the program and creates two 6-decimal SPL Token mints, giving the
+/// maker `maker_a` mint_a and the taker `taker_b` mint_b in their ATAs.
+pub fn setup(maker_a: u64, taker_b: u64) -> Env {
+    let mut ctx = load_program();
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = ctx.svm.create_token_mint(&maker, 6).unwrap().pubkey();
+    let mint_b = ctx.svm.create_token_mint(&maker, 6).unwrap().pubkey();
+
+    let maker_ata_a = ctx.svm.create_associated_token_account(&mint_a, &maker).unwrap();
+    ctx.svm.mint_to(&mint_a, &maker_ata_a, &maker, maker_a).unwrap();
+    let taker_ata_b = ctx.svm.create_associated_token_account(&mint_b, &taker).unwrap();
+    ctx.svm.mint_to(&mint_b, &taker_ata_b, &maker, taker_b).unwrap();
+
+    Env { ctx, maker, taker, mint_a, mint_b, token_program: spl_token::id() }
+}
+
+impl Env {
+    pub fn escrow(&self, seed: u64) -> Pubkey {
+        escrow(&self.maker.pubkey(), seed)
+    }
+
+    pub fn make_accounts(&self, seed: u64) -> accounts::Make {
+        make_accounts(&self.maker.pubkey(), seed, &self.mint_a, &self.mint_b, &self.token_program)
+    }
+
+    pub fn take_accounts(&self, seed: u64) -> accounts::Take {
+        take_accounts(
+            &self.taker.pubkey(),
+            &self.maker.pubkey(),
+            &self.escrow(seed),
+            &self.mint_a,
+            &self.mint_b,
+            &self.token_program,
+        )
+    }
+
+    /// Makes escrow `seed` offering `amount` mint_a for `receive` mint_b.
+    pub fn make(
+        &mut self,
+        seed: u64,
+        receive: u64,
+        amount: u64,
+        extensions: Vec<EscrowExtension>,
+    ) -> TransactionResult {
+        let maker = self.maker.insecure_clone();
+        self.execute(
+            self.make_accounts(seed),
+            args::Make { seed, receive, amount, extensions, accepted_hazards: 0 },
+            &maker,
+        )
+    }
+
+    /// A plain full take of escrow `seed` by the taker.
+    pub fn take(&mut self, seed: u64) -> TransactionResult {
+        let taker = self.taker.insecure_clone();
+        self.execute(
+            self.take_accounts(seed),
+            args::Take { referral_bps: 0, allowlist: None },
+            &taker,
+        )
+    }
+
+    pub fn execute(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        args: impl InstructionData,
+        signer: &Keypair,
+    ) -> TransactionResult {
+        let ix = self.ctx.program().accounts(accounts).args(args).instruction().unwrap();
+        self.ctx.execute_instruction(ix, &[signer]).unwrap()
+    }
+}
+
+/// Re-deploys the program under the upgradeable loader with `authority` as
+/// its upgrade authority. `AnchorLiteSVM` loads programs as non-upgradeable,
+/// leaving no `ProgramData` for `initialize_config` to check.
+pub fn set_upgrade_authority(svm: &mut LiteSVM, authority: &Pubkey) {
+    let program_id = crate::anchor_escrow::ID;
+    let elf = svm.get_account(&program_id).unwrap().data;
+    let program_data = get_program_data_address(&program_id);
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    let mut data_account = Account::new_data_with_space(
+        svm.minimum_balance_for_rent_exemption(metadata_len + elf.len()),
+        &UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(*authority),
+        },
+        metadata_len + elf.len(),
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    data_account.data[metadata_len..].copy_from_slice(&elf);
+    svm.set_account(program_data, data_account).unwrap();
+
+    let mut program = Account::new_data(
+        svm.minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program()),
+        &UpgradeableLoaderState::Program { programdata_address: program_data },
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    program.executable = true;
+    svm.set_account(program_id, program).unwrap();
+}
+
+pub fn initialize_config_ix(admin: &Pubkey) -> Instruction {
+    let program_id = crate::anchor_escrow::ID;
+    Instruction {
+        program_id,
+        accounts: crate::anchor_escrow::client::accounts::InitializeConfig {
+            admin: *admin,
+            config: config(),
+            program: program_id,
+            program_data: get_program_data_address(&program_id),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: crate::anchor_escrow::client::args::InitializeConfig {}.data(),
+    }
+}
+
+/// Makes `admin` the upgrade authority and has it create the config.
+pub fn initialize_config(svm: &mut LiteSVM, admin: &Keypair) {
+    set_upgrade_authority(svm, &admin.pubkey());
+    send(svm, &[initialize_config_ix(&admin.pubkey())], admin, &[]);
+}
+
+pub fn send(svm: &mut LiteSVM, ixs: &[Instruction], payer: &Keypair, signers: &[&Keypair]) {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&payer.pubkey()),
+        &all_signers,
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx).unwrap();
+}
+
+/// Creates a Token-2022 mint with room for `extensions`. `extension_ixs` must
+/// initialise them and run before `InitializeMint2`, as the token program
+/// requires.
+pub fn create_token_2022_mint(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    decimals: u8,
+    extensions: &[ExtensionType],
+    extension_ixs: impl FnOnce(&Pubkey) -> Vec<Instruction>,
+    freeze_authority: Option<&Pubkey>,
+) -> Pubkey {
+    let mint = Keypair::new();
+    let space = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+    let rent = svm.minimum_balance_for_rent_exemption(space);
+
+    let mut ixs = vec![system_instruction::create_account(
+        &authority.pubkey(),
+        &mint.pubkey(),
+        rent,
+        space as u64,
+        &spl_token_2022::id(),
+    )];
+    ixs.extend(extension_ixs(&mint.pubkey()));
+    ixs.push(
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            &authority.pubkey(),
+            freeze_authority,
+            decimals,
+        )
+        .unwrap(),
+    );
+    send(svm, &ixs, authority, &[&mint]);
+    mint.pubkey()
+}
+
+/// Creates (if needed) and funds `owner`'s Token-2022 ATA for `mint`.
+pub fn create_token_2022_ata(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Pubkey {
+    let ata = get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::id());
+    if svm.get_account(&ata).is_none() {
+        let ix = create_associated_token_account(&payer.pubkey(), owner, mint, &spl_token_2022::id());
+        send(svm, &[ix], payer, &[]);
+    }
+    ata
+}
+
+pub fn mint_token_2022(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) {
+    let ix = spl_token_2022::instruction::mint_to(
+        &spl_token_2022::id(),
+        mint,
+        destination,
+        &authority.pubkey(),
+        &[],
+        amount,
+    )
+    .unwrap();
+    send(svm, &[ix], authority, &[]);
+}
+
+pub fn token_2022_ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::id())
+}
+
+/// Creates a Token-2022 collection: a 0-decimal group mint, pointing at
+/// itself, whose update authority is `owner`.
+pub fn create_token_2022_collection(svm: &mut LiteSVM, owner: &Keypair) -> Pubkey {
+    let authority = owner.pubkey();
+    let collection = create_token_2022_mint(
+        svm,
+        owner,
+        0,
+        &[ExtensionType::GroupPointer],
+        |mint| {
+            vec![group_pointer::instruction::initialize(
+                &spl_token_2022::id(),
+                mint,
+                Some(authority),
+                Some(*mint),
+            )
+            .unwrap()]
+        },
+        None,
+    );
+    let ixs = [
+        system_instruction::transfer(&authority, &collection, 10_000_000),
+        spl_token_group_interface::instruction::initialize_group(
+            &spl_token_2022::id(),
+            &collection,
+            &collection,
+            &authority,
+            Some(authority),
+            100,
+        ),
+    ];
+    send(svm, &ixs, owner, &[]);
+    collection
+}
+
+/// Creates a Token-2022 NFT: 0 decimals, a supply of 1 held in `owner`'s ATA,
+/// and on-mint metadata carrying `fields` as additional metadata. With
+/// `collection`, made by `owner`, the NFT is also a member of it.
+pub fn create_token_2022_nft(
+    svm: &mut LiteSVM,
+    owner: &Keypair,
+    fields: &[(String, String)],
+    collection: Option<&Pubkey>,
+) -> Pubkey {
+    let authority = owner.pubkey();
+    let mut extensions = vec![ExtensionType::MetadataPointer];
+    if collection.is_some() {
+        extensions.push(ExtensionType::GroupMemberPointer);
+    }
+    let mint = create_token_2022_mint(
+        svm,
+        owner,
+        0,
+        &extensions,
+        |mint| {
+            let mut ixs = vec![metadata_pointer::instruction::initialize(
+                &spl_token_2022::id(),
+                mint,
+                Some(authority),
+                Some(*mint),
+            )
+            .unwrap()];
+            if collection.is_some() {
+                ixs.push(
+                    group_member_pointer::instruction::initialize(
+                        &spl_token_2022::id(),
+                        mint,
+                        Some(authority),
+                        Some(*mint),
+                    )
+                    .unwrap(),
+                );
+            }
+            ixs
+        },
+        None,
+    );
+
+    // Token-2022 grows the mint for metadata but the payer must fund the rent
+    let mut ixs = vec![
+        system_instruction::transfer(&authority, &mint, 10_000_000),
+        spl_token_metadata_interface::instruction::initialize(
+            &spl_token_2022::id(),
+            &mint,
+            &authority,
+            &mint,
+            &authority,
+            "Escrowed".to_string(),
+            "NFT".to_string(),
+            "https://example.com/nft.json".to_string(),
+        ),
+    ];
+    ixs.extend(fields.iter().map(|(key, value)| {
+        spl_token_metadata_interface::instruction::update_field(
+            &spl_token_2022::id(),
+            &mint,
+            &authority,
+            Field::Key(key.clone()),
+            value.clone(),
+        )
+    }));
+    if let Some(collection) = collection {
+        ixs.push(spl_token_group_interface::instruction::initialize_member(
+            &spl_token_2022::id(),
+            &mint,
+            &mint,
+            &authority,
+            collection,
+            &authority,
+        ));
+    }
+    send(svm, &ixs, owner, &[]);
+
+    let ata = create_token_2022_ata(svm, owner, &authority, &mint);
+    mint_token_2022(svm, owner, &mint, &ata, 1);
+    mint
+}
@@ -0,0 +1,305 @@
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+use anchor_escrow::types::{
+    EscrowExtension, MerkleAllowlist, ProceedsDestination, ProceedsSplit, SplitRecipient,
+    TokenGate,
+};
+
+mod common;
+
+/// Everything a delegated order test needs after `make_delegated` succeeded.
+struct DelegatedOrder {
+    ctx: AnchorContext,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    escrow_pda: Pubkey,
+    maker_ata_a: Pubkey,
+    taker_ata_b: Pubkey,
+}
+
+fn setup_delegated_order() -> DelegatedOrder {
+    let program_id = anchor_escrow::ID;
+    let mut ctx = common::load_program();
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap(); // 10 SOL
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap(); // 10 SOL
+
+    let mint_a = ctx.svm.create_token_mint(&maker, 9).unwrap();
+    let mint_b = ctx.svm.create_token_mint(&maker, 9).unwrap();
+
+    let maker_ata_a = ctx.svm
+        .create_associated_token_account(&mint_a.pubkey(), &maker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_a.pubkey(), &maker_ata_a, &maker, 1_000_000_000)
+        .unwrap(); // 1.0 tokens
+
+    let taker_ata_b = ctx.svm
+        .create_associated_token_account(&mint_b.pubkey(), &taker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_b.pubkey(), &taker_ata_b, &maker, 500_000_000)
+        .unwrap(); // 0.5 tokens
+
+    let seed: u64 = 7;
+    let escrow_pda = ctx.svm.get_pda(
+        &[b"escrow", maker.pubkey().as_ref(), &seed.to_le_bytes()],
+        &program_id,
+    );
+
+    // No vault: make_delegated only records terms and approves the escrow PDA
+    let make_ix = ctx.program()
+        .accounts(common::make_delegated_accounts(
+            &maker.pubkey(),
+            seed,
+            &mint_a.pubkey(),
+            &mint_b.pubkey(),
+            &spl_token::id(),
+        ))
+        .args(anchor_escrow::client::args::MakeDelegated {
+            seed,
+            receive: 500_000_000,  // 0.5 tokens
+            amount: 1_000_000_000, // 1 token
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap();
+
+    ctx.execute_instruction(make_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    DelegatedOrder {
+        ctx,
+        maker,
+        taker,
+        mint_a: mint_a.pubkey(),
+        mint_b: mint_b.pubkey(),
+        escrow_pda,
+        maker_ata_a,
+        taker_ata_b,
+    }
+}
+
+fn take_delegated_ix(order: &DelegatedOrder) -> solana_sdk::instruction::Instruction {
+    order.ctx.program()
+        .accounts(anchor_escrow::client::accounts::TakeDelegated {
+            taker: order.taker.pubkey(),
+            maker: order.maker.pubkey(),
+            escrow: order.escrow_pda,
+            mint_a: order.mint_a,
+            mint_b: order.mint_b,
+            maker_ata_a: order.maker_ata_a,
+            taker_ata_a: get_associated_token_address(&order.taker.pubkey(), &order.mint_a),
+            taker_ata_b: order.taker_ata_b,
+            maker_ata_b: get_associated_token_address(&order.maker.pubkey(), &order.mint_b),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: common::maker_state(&order.maker.pubkey()),
+            pair_registry: common::pair_registry(&order.mint_a, &order.mint_b),
+            maker_registry: common::maker_registry(&order.maker.pubkey()),
+            config: common::config(),
+        })
+        .args(anchor_escrow::client::args::TakeDelegated {})
+        .instruction()
+        .unwrap()
+}
+
+#[test]
+fn test_make_and_take_delegated() {
+    let mut order = setup_delegated_order();
+
+    // The maker keeps custody until the take: tokens stay in maker_ata_a
+    order.ctx.svm.assert_token_balance(&order.maker_ata_a, 1_000_000_000);
+    let maker_ata_a_state = litesvm_token::get_spl_account::<spl_token::state::Account>(
+        &order.ctx.svm,
+        &order.maker_ata_a,
+    )
+    .unwrap();
+    assert_eq!(maker_ata_a_state.delegate, Some(order.escrow_pda).into());
+    assert_eq!(maker_ata_a_state.delegated_amount, 1_000_000_000);
+
+    let take_ix = take_delegated_ix(&order);
+    let taker = order.taker.insecure_clone();
+    order.ctx.execute_instruction(take_ix, &[&taker])
+        .unwrap()
+        .assert_success();
+
+    order.ctx.svm.assert_account_closed(&order.escrow_pda);
+    order.ctx.svm.assert_token_balance(&order.maker_ata_a, 0);
+    order.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&order.taker.pubkey(), &order.mint_a),
+        1_000_000_000,
+    );
+    order.ctx.svm.assert_token_balance(&order.taker_ata_b, 0);
+    order.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&order.maker.pubkey(), &order.mint_b),
+        500_000_000,
+    );
+}
+
+#[test]
+fn test_take_delegated_fails_after_revoke() {
+    let mut order = setup_delegated_order();
+
+    // Maker pulls the approval out from under the order
+    let revoke_ix = spl_token::instruction::revoke(
+        &spl_token::id(),
+        &order.maker_ata_a,
+        &order.maker.pubkey(),
+        &[],
+    )
+    .unwrap();
+    let maker = order.maker.insecure_clone();
+    order.ctx.execute_instruction(revoke_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    let take_ix = take_delegated_ix(&order);
+    let taker = order.taker.insecure_clone();
+    order.ctx.execute_instruction(take_ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("InsufficientAllowance");
+
+    // Nothing moved and the order is still on-chain
+    order.ctx.svm.assert_account_exists(&order.escrow_pda);
+    order.ctx.svm.assert_token_balance(&order.taker_ata_b, 500_000_000);
+}
+
+#[test]
+fn test_take_delegated_fails_when_maker_balance_spent() {
+    let mut order = setup_delegated_order();
+
+    // Maker moves most of the balance elsewhere while the approval stays in place
+    let sink = order.ctx.svm.create_funded_account(1_000_000_000).unwrap();
+    let sink_ata_a = order.ctx.svm
+        .create_associated_token_account(&order.mint_a, &sink)
+        .unwrap();
+    let transfer_ix = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &order.maker_ata_a,
+        &sink_ata_a,
+        &order.maker.pubkey(),
+        &[],
+        600_000_000,
+    )
+    .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&order.maker.pubkey()),
+        &[&order.maker],
+        order.ctx.latest_blockhash(),
+    );
+    order.ctx.svm.send_transaction(tx).unwrap();
+
+    let take_ix = take_delegated_ix(&order);
+    let taker = order.taker.insecure_clone();
+    order.ctx.execute_instruction(take_ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("InsufficientBalance");
+
+    order.ctx.svm.assert_token_balance(&order.taker_ata_b, 500_000_000);
+}
+
+#[test]
+fn test_cancel_delegated_revokes_approval() {
+    let mut order = setup_delegated_order();
+
+    let cancel_ix = order.ctx.program()
+        .accounts(anchor_escrow::client::accounts::CancelDelegated {
+            maker: order.maker.pubkey(),
+            escrow: order.escrow_pda,
+            mint_a: order.mint_a,
+            maker_ata_a: order.maker_ata_a,
+            token_program: spl_token::id(),
+            maker_state: common::maker_state(&order.maker.pubkey()),
+            pair_registry: common::pair_registry(&order.mint_a, &order.mint_b),
+            maker_registry: common::maker_registry(&order.maker.pubkey()),
+        })
+        .args(anchor_escrow::client::args::CancelDelegated {})
+        .instruction()
+        .unwrap();
+    let maker = order.maker.insecure_clone();
+    order.ctx.execute_instruction(cancel_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    order.ctx.svm.assert_account_closed(&order.escrow_pda);
+    let state: anchor_escrow::accounts::MakerState = order.ctx
+        .get_account(&common::maker_state(&order.maker.pubkey()))
+        .unwrap();
+    assert_eq!(state.open_escrows, 0);
+    let maker_ata_a_state = litesvm_token::get_spl_account::<spl_token::state::Account>(
+        &order.ctx.svm,
+        &order.maker_ata_a,
+    )
+    .unwrap();
+    assert_eq!(maker_ata_a_state.delegate, None.into());
+    assert_eq!(maker_ata_a_state.delegated_amount, 0);
+}
+
+fn add_extensions(order: &mut DelegatedOrder, extensions: Vec<EscrowExtension>) -> litesvm_utils::TransactionResult {
+    let ix = order.ctx.program()
+        .accounts(anchor_escrow::client::accounts::AddExtensions {
+            maker: order.maker.pubkey(),
+            escrow: order.escrow_pda,
+            system_program: system_program::id(),
+        })
+        .args(anchor_escrow::client::args::AddExtensions { extensions })
+        .instruction()
+        .unwrap();
+    let maker = order.maker.insecure_clone();
+    order.ctx.execute_instruction(ix, &[&maker]).unwrap()
+}
+
+#[test]
+fn test_delegated_escrow_refuses_proceeds_routing() {
+    let mut order = setup_delegated_order();
+
+    // take_delegated always pays the maker's ATA, so either would be ignored
+    let treasury_b = Pubkey::new_unique();
+    add_extensions(
+        &mut order,
+        vec![EscrowExtension::ProceedsDestination(ProceedsDestination { token_account: treasury_b })],
+    )
+    .assert_anchor_error("InvalidEscrowKind");
+    let split = ProceedsSplit {
+        recipients: vec![SplitRecipient { token_account: treasury_b, bps: 10_000 }],
+    };
+    add_extensions(&mut order, vec![EscrowExtension::ProceedsSplit(split)])
+        .assert_anchor_error("InvalidEscrowKind");
+}
+
+#[test]
+fn test_delegated_escrow_refuses_token_gate() {
+    let mut order = setup_delegated_order();
+
+    // take_delegated passes no gate account, so the escrow could never fill
+    let gate = TokenGate { mint: order.mint_b, min_balance: 1, non_transferable: false };
+    add_extensions(&mut order, vec![EscrowExtension::TokenGate(gate)])
+        .assert_anchor_error("InvalidEscrowKind");
+}
+
+#[test]
+fn test_delegated_escrow_refuses_merkle_allowlist() {
+    let mut order = setup_delegated_order();
+
+    // take_delegated passes no proof, so no taker could ever qualify
+    let allowlist = MerkleAllowlist { root: [7; 32] };
+    add_extensions(&mut order, vec![EscrowExtension::MerkleAllowlist(allowlist)])
+        .assert_anchor_error("InvalidEscrowKind");
+}
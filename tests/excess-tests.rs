@@ -0,0 +1,133 @@
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{pubkey::Pubkey, signature::Signer, system_program};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+const SEED: u64 = 5;
+
+/// Escrows 1000 mint_a for 2000 mint_b, then donates 500 mint_a into the vault
+fn setup() -> common::Env {
+    let mut env = common::setup(1_000, 2_000);
+    env.make(SEED, 2_000, 1_000, vec![]).assert_success();
+
+    // Anyone can send tokens straight to the vault ATA
+    let maker = env.maker.insecure_clone();
+    let vault = vault(&env);
+    env.ctx.svm.mint_to(&env.mint_a, &vault, &maker, 500).unwrap();
+    env
+}
+
+fn vault(env: &common::Env) -> Pubkey {
+    get_associated_token_address(&env.escrow(SEED), &env.mint_a)
+}
+
+fn maker_ata_a(env: &common::Env) -> Pubkey {
+    get_associated_token_address(&env.maker.pubkey(), &env.mint_a)
+}
+
+fn sweep_excess(env: &mut common::Env) -> litesvm_utils::TransactionResult {
+    let maker = env.maker.insecure_clone();
+    env.execute(
+        anchor_escrow::client::accounts::SweepExcess {
+            maker: maker.pubkey(),
+            escrow: env.escrow(SEED),
+            mint_a: env.mint_a,
+            vault: vault(env),
+            maker_ata_a: maker_ata_a(env),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: common::maker_state(&maker.pubkey()),
+            pair_registry: common::pair_registry(&env.mint_a, &env.mint_b),
+            maker_registry: common::maker_registry(&maker.pubkey()),
+        },
+        anchor_escrow::client::args::SweepExcess {},
+        &maker,
+    )
+}
+
+#[test]
+fn test_make_records_deposited_amount() {
+    let env = setup();
+
+    let escrow: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow(SEED)).unwrap();
+    assert_eq!(escrow.deposited, 1_000);
+    assert_eq!(escrow.filled, 0);
+    assert!(matches!(escrow.status, anchor_escrow::types::EscrowStatus::Open));
+    assert_eq!(escrow.created_at, escrow.updated_at);
+    env.ctx.svm.assert_token_balance(&vault(&env), 1_500);
+}
+
+#[test]
+fn test_take_ignores_donation_then_sweep_closes() {
+    let mut env = setup();
+    let taker = env.taker.insecure_clone();
+    let taker_ata_a = get_associated_token_address(&env.taker.pubkey(), &env.mint_a);
+
+    let take_ix = env.ctx.program()
+        .accounts(env.take_accounts(SEED))
+        .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(take_ix.clone(), &[&taker])
+        .unwrap()
+        .assert_success();
+
+    // The taker gets exactly what was escrowed; the donation keeps the escrow open
+    env.ctx.svm.assert_token_balance(&taker_ata_a, 1_000);
+    env.ctx.svm.assert_token_balance(&vault(&env), 500);
+    let escrow: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow(SEED)).unwrap();
+    assert_eq!(escrow.filled, 1_000);
+    assert_eq!(escrow.fill_count, 1);
+    assert!(matches!(escrow.status, anchor_escrow::types::EscrowStatus::Filled));
+
+    // A filled escrow cannot be taken again or refunded, only swept
+    env.ctx.svm.expire_blockhash();
+    env.ctx.execute_instruction(take_ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("EscrowAlreadyFilled");
+    let refund_ix = env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::Refund {
+            maker: env.maker.pubkey(),
+            escrow: env.escrow(SEED),
+            mint_a: env.mint_a,
+            vault: vault(&env),
+            maker_ata_a: maker_ata_a(&env),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: common::maker_state(&env.maker.pubkey()),
+            pair_registry: common::pair_registry(&env.mint_a, &env.mint_b),
+            maker_registry: common::maker_registry(&env.maker.pubkey()),
+        })
+        .args(anchor_escrow::client::args::Refund {})
+        .instruction()
+        .unwrap();
+    let maker = env.maker.insecure_clone();
+    env.ctx.execute_instruction(refund_ix, &[&maker])
+        .unwrap()
+        .assert_anchor_error("EscrowAlreadyFilled");
+
+    sweep_excess(&mut env).assert_success();
+    env.ctx.svm.assert_token_balance(&maker_ata_a(&env), 500);
+    env.ctx.svm.assert_account_closed(&vault(&env));
+    env.ctx.svm.assert_account_closed(&env.escrow(SEED));
+}
+
+#[test]
+fn test_sweep_leaves_open_escrow_intact() {
+    let mut env = setup();
+
+    sweep_excess(&mut env).assert_success();
+    env.ctx.svm.assert_token_balance(&maker_ata_a(&env), 500);
+    env.ctx.svm.assert_token_balance(&vault(&env), 1_000);
+    env.ctx.svm.assert_account_exists(&env.escrow(SEED));
+
+    // Nothing left to sweep
+    sweep_excess(&mut env).assert_anchor_error("NoExcess");
+}
@@ -0,0 +1,120 @@
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+use anchor_escrow::types::{AllowedTaker, EscrowExtension, Expiry, Memo};
+
+const EXPIRY: i64 = 1_000_000;
+
+const SEED: u64 = 9;
+
+/// Makes an escrow that expires at `EXPIRY`, may only be taken by the taker
+/// and carries a memo
+fn setup() -> common::Env {
+    let mut env = common::setup(1_000, 2_000);
+
+    let mut clock = env.ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = EXPIRY - 100;
+    env.ctx.svm.set_sysvar(&clock);
+
+    let extensions = vec![
+        EscrowExtension::Expiry(Expiry { unix_timestamp: EXPIRY }),
+        EscrowExtension::AllowedTaker(AllowedTaker { taker: env.taker.pubkey() }),
+        EscrowExtension::Memo(Memo { memo: "otc desk".to_string() }),
+    ];
+    env.make(SEED, 2_000, 1_000, extensions).assert_success();
+    env
+}
+
+/// A full take by `taker`
+fn take_ix(env: &common::Env, taker: &Keypair) -> Instruction {
+    env.ctx.program()
+        .accounts(common::take_accounts(
+            &taker.pubkey(),
+            &env.maker.pubkey(),
+            &env.escrow(SEED),
+            &env.mint_a,
+            &env.mint_b,
+            &spl_token::id(),
+        ))
+        .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+        .instruction()
+        .unwrap()
+}
+
+#[test]
+fn test_only_allowed_taker_can_fill() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let stranger = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let stranger_ata_b = env.ctx.svm.create_associated_token_account(&env.mint_b, &stranger).unwrap();
+    env.ctx.svm.mint_to(&env.mint_b, &stranger_ata_b, &maker, 2_000).unwrap();
+
+    let ix = take_ix(&env, &stranger);
+    env.ctx.execute_instruction(ix, &[&stranger])
+        .unwrap()
+        .assert_anchor_error("TakerNotAllowed");
+
+    let taker = env.taker.insecure_clone();
+    let ix = take_ix(&env, &taker);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_success();
+    env.ctx.svm.assert_account_closed(&env.escrow(SEED));
+}
+
+#[test]
+fn test_expired_escrow_cannot_be_taken() {
+    let mut env = setup();
+    let taker = env.taker.insecure_clone();
+
+    let mut clock = env.ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = EXPIRY + 1;
+    env.ctx.svm.set_sysvar(&clock);
+
+    let ix = take_ix(&env, &taker);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("OrderExpired");
+}
+
+#[test]
+fn test_add_extensions_grows_account() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let before = env.ctx.svm.get_account(&env.escrow(SEED)).unwrap().data.len();
+
+    let longer_memo = "otc desk, settle by friday".to_string();
+    let ix = env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::AddExtensions {
+            maker: env.maker.pubkey(),
+            escrow: env.escrow(SEED),
+            system_program: system_program::id(),
+        })
+        .args(anchor_escrow::client::args::AddExtensions {
+            extensions: vec![EscrowExtension::Memo(Memo { memo: longer_memo.clone() })],
+        })
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    // The memo is replaced in place, not duplicated
+    let after = env.ctx.svm.get_account(&env.escrow(SEED)).unwrap().data.len();
+    assert_eq!(after, before + longer_memo.len() - "otc desk".len());
+
+    // The fixed part still deserializes
+    let escrow: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow(SEED)).unwrap();
+    assert_eq!(escrow.deposited, 1_000);
+}
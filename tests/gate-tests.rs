@@ -0,0 +1,118 @@
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token_2022::{extension::ExtensionType, instruction::initialize_non_transferable_mint};
+
+use common::{create_token_2022_ata, create_token_2022_mint, mint_token_2022};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+use anchor_escrow::types::{EscrowExtension, TokenGate};
+
+mod common;
+
+const SEED: u64 = 9;
+
+/// An escrow of 1_000 mint_a for 1_000 mint_b, gated by `gate`.
+fn setup(gate: impl FnOnce(&mut common::Env) -> TokenGate) -> common::Env {
+    let mut env = common::setup(1_000, 1_000);
+    let gate = gate(&mut env);
+    env.make(SEED, 1_000, 1_000, vec![EscrowExtension::TokenGate(gate)])
+        .assert_success();
+    env
+}
+
+/// A legacy SPL gate mint; the taker holds `balance` of it.
+fn spl_gate(env: &mut common::Env, balance: u64) -> (Pubkey, Pubkey) {
+    let mint = env.ctx.svm.create_token_mint(&env.maker, 0).unwrap().pubkey();
+    let account = env.ctx.svm.create_associated_token_account(&mint, &env.taker).unwrap();
+    if balance > 0 {
+        env.ctx.svm.mint_to(&mint, &account, &env.maker, balance).unwrap();
+    }
+    (mint, account)
+}
+
+fn take(env: &mut common::Env, taker_gate: Option<Pubkey>) -> litesvm_utils::TransactionResult {
+    let taker = env.taker.insecure_clone();
+    env.execute(
+        anchor_escrow::client::accounts::Take { taker_gate, ..env.take_accounts(SEED) },
+        anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None },
+        &taker,
+    )
+}
+
+#[test]
+fn test_gate_requires_minimum_balance() {
+    let mut gate_account = Pubkey::default();
+    let mut gate_mint = Pubkey::default();
+    let mut env = setup(|env| {
+        (gate_mint, gate_account) = spl_gate(env, 4);
+        TokenGate { mint: gate_mint, min_balance: 5, non_transferable: false }
+    });
+
+    take(&mut env, None).assert_anchor_error("TokenGateMissing");
+    take(&mut env, Some(gate_account)).assert_anchor_error("TokenGateInsufficient");
+
+    let maker = env.maker.insecure_clone();
+    env.ctx.svm.mint_to(&gate_mint, &gate_account, &maker, 1).unwrap();
+    take(&mut env, Some(gate_account)).assert_success();
+    env.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&env.taker.pubkey(), &env.mint_a),
+        1_000,
+    );
+    // The gate is only checked, never spent
+    env.ctx.svm.assert_token_balance(&gate_account, 5);
+}
+
+#[test]
+fn test_gate_account_must_be_takers_and_of_gate_mint() {
+    let mut gate_mint = Pubkey::default();
+    let mut env = setup(|env| {
+        gate_mint = spl_gate(env, 0).0;
+        TokenGate { mint: gate_mint, min_balance: 1, non_transferable: false }
+    });
+
+    // Someone else's well-funded gate account does not count
+    let maker = env.maker.insecure_clone();
+    let maker_gate = env.ctx.svm.create_associated_token_account(&gate_mint, &maker).unwrap();
+    env.ctx.svm.mint_to(&gate_mint, &maker_gate, &maker, 10).unwrap();
+    take(&mut env, Some(maker_gate)).assert_anchor_error("TokenGateMissing");
+
+    // Nor does the taker's balance of another mint
+    let taker_ata_b = get_associated_token_address(&env.taker.pubkey(), &env.mint_b);
+    take(&mut env, Some(taker_ata_b)).assert_anchor_error("TokenGateMissing");
+}
+
+#[test]
+fn test_non_transferable_membership_gate() {
+    let mut gate_account = Pubkey::default();
+    let mut env = setup(|env| {
+        let maker = env.maker.insecure_clone();
+        let taker = env.taker.pubkey();
+        let mint = create_token_2022_mint(
+            &mut env.ctx.svm,
+            &maker,
+            0,
+            &[ExtensionType::NonTransferable],
+            |mint| vec![initialize_non_transferable_mint(&spl_token_2022::id(), mint).unwrap()],
+            None,
+        );
+        gate_account = create_token_2022_ata(&mut env.ctx.svm, &maker, &taker, &mint);
+        mint_token_2022(&mut env.ctx.svm, &maker, &mint, &gate_account, 1);
+        TokenGate { mint, min_balance: 1, non_transferable: true }
+    });
+
+    take(&mut env, Some(gate_account)).assert_success();
+}
+
+#[test]
+fn test_non_transferable_gate_refuses_transferable_mint() {
+    let mut gate_account = Pubkey::default();
+    let mut env = setup(|env| {
+        let (mint, account) = spl_gate(env, 1);
+        gate_account = account;
+        TokenGate { mint, min_balance: 1, non_transferable: true }
+    });
+
+    take(&mut env, Some(gate_account)).assert_anchor_error("TokenGateTransferable");
+}
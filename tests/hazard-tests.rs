@@ -0,0 +1,166 @@
+mod common;
+
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_token_2022::{
+    extension::{default_account_state::instruction::initialize_default_account_state, ExtensionType},
+    instruction::{initialize_non_transferable_mint, initialize_permanent_delegate},
+    state::AccountState,
+};
+
+use common::{create_token_2022_ata, create_token_2022_mint, mint_token_2022};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+// Mirrors `token_ext::hazard` in the program
+const PERMANENT_DELEGATE: u8 = 1 << 0;
+const NON_TRANSFERABLE: u8 = 1 << 1;
+const FREEZE_AUTHORITY: u8 = 1 << 3;
+
+struct Env {
+    ctx: AnchorContext,
+    maker: Keypair,
+    /// A plain Token-2022 mint the maker holds
+    safe_mint: Pubkey,
+}
+
+fn setup() -> Env {
+    let mut ctx = common::load_program();
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let safe_mint = create_token_2022_mint(&mut ctx.svm, &maker, 6, &[], |_| vec![], None);
+    let maker_ata = create_token_2022_ata(&mut ctx.svm, &maker, &maker.pubkey(), &safe_mint);
+    mint_token_2022(&mut ctx.svm, &maker, &safe_mint, &maker_ata, 1_000_000);
+
+    Env { ctx, maker, safe_mint }
+}
+
+impl Env {
+    fn hostile_mint(
+        &mut self,
+        extension: ExtensionType,
+        init: impl FnOnce(&Pubkey) -> Vec<Instruction>,
+        freeze_authority: Option<&Pubkey>,
+    ) -> Pubkey {
+        let maker = self.maker.insecure_clone();
+        create_token_2022_mint(&mut self.ctx.svm, &maker, 6, &[extension], init, freeze_authority)
+    }
+
+    fn make(&mut self, seed: u64, mint_a: Pubkey, mint_b: Pubkey, accepted_hazards: u8) -> litesvm_utils::TransactionResult {
+        let maker = self.maker.insecure_clone();
+        let ix = self.ctx.program()
+            .accounts(common::make_accounts(&maker.pubkey(), seed, &mint_a, &mint_b, &spl_token_2022::id()))
+            .args(anchor_escrow::client::args::Make {
+                seed,
+                receive: 100,
+                amount: 100,
+                extensions: vec![],
+                accepted_hazards,
+            })
+            .instruction()
+            .unwrap();
+        self.ctx.execute_instruction(ix, &[&maker]).unwrap()
+    }
+}
+
+#[test]
+fn test_permanent_delegate_is_refused_unless_accepted() {
+    let mut env = setup();
+    let delegate = Pubkey::new_unique();
+    let hostile = env.hostile_mint(
+        ExtensionType::PermanentDelegate,
+        |mint| vec![initialize_permanent_delegate(&spl_token_2022::id(), mint, &delegate).unwrap()],
+        None,
+    );
+
+    env.make(1, env.safe_mint, hostile, 0)
+        .assert_anchor_error("MintHasPermanentDelegate");
+    env.make(1, env.safe_mint, hostile, PERMANENT_DELEGATE)
+        .assert_success();
+}
+
+#[test]
+fn test_non_transferable_mint_is_refused() {
+    let mut env = setup();
+    let hostile = env.hostile_mint(
+        ExtensionType::NonTransferable,
+        |mint| vec![initialize_non_transferable_mint(&spl_token_2022::id(), mint).unwrap()],
+        None,
+    );
+
+    env.make(1, env.safe_mint, hostile, 0)
+        .assert_anchor_error("MintNonTransferable");
+    env.make(1, env.safe_mint, hostile, NON_TRANSFERABLE)
+        .assert_success();
+}
+
+#[test]
+fn test_default_frozen_mint_is_refused() {
+    let mut env = setup();
+    // Token-2022 only allows frozen-by-default mints that have a freeze authority
+    let authority = env.maker.pubkey();
+    let hostile = env.hostile_mint(
+        ExtensionType::DefaultAccountState,
+        |mint| {
+            vec![initialize_default_account_state(
+                &spl_token_2022::id(),
+                mint,
+                &AccountState::Frozen,
+            )
+            .unwrap()]
+        },
+        Some(&authority),
+    );
+
+    env.make(1, env.safe_mint, hostile, 0)
+        .assert_anchor_error("MintHasFreezeAuthority");
+    // Accepting the freeze authority alone still leaves the frozen default
+    env.make(1, env.safe_mint, hostile, FREEZE_AUTHORITY)
+        .assert_anchor_error("MintDefaultFrozen");
+}
+
+#[test]
+fn test_freeze_authority_on_deposited_mint_is_refused_unless_accepted() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let mint_a = create_token_2022_mint(&mut env.ctx.svm, &maker, 6, &[], |_| vec![], Some(&maker.pubkey()));
+    let maker_ata_a = create_token_2022_ata(&mut env.ctx.svm, &maker, &maker.pubkey(), &mint_a);
+    mint_token_2022(&mut env.ctx.svm, &maker, &mint_a, &maker_ata_a, 1_000);
+
+    env.make(1, mint_a, env.safe_mint, 0)
+        .assert_anchor_error("MintHasFreezeAuthority");
+    env.ctx.svm.assert_token_balance(&maker_ata_a, 1_000);
+
+    env.make(1, mint_a, env.safe_mint, FREEZE_AUTHORITY)
+        .assert_success();
+    env.ctx.svm.assert_token_balance(&maker_ata_a, 900);
+}
+
+#[test]
+fn test_delegated_make_refuses_hazardous_mints() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let delegate = Pubkey::new_unique();
+    let hostile = env.hostile_mint(
+        ExtensionType::PermanentDelegate,
+        |mint| vec![initialize_permanent_delegate(&spl_token_2022::id(), mint, &delegate).unwrap()],
+        None,
+    );
+
+    let mut make_delegated = |accepted_hazards| {
+        let ix = env.ctx.program()
+            .accounts(common::make_delegated_accounts(&maker.pubkey(), 1, &env.safe_mint, &hostile, &spl_token_2022::id()))
+            .args(anchor_escrow::client::args::MakeDelegated { seed: 1, receive: 100, amount: 100, accepted_hazards })
+            .instruction()
+            .unwrap();
+        env.ctx.execute_instruction(ix, &[&maker]).unwrap()
+    };
+    make_delegated(0).assert_anchor_error("MintHasPermanentDelegate");
+    make_delegated(PERMANENT_DELEGATE).assert_success();
+}
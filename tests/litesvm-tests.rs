@@ -103,6 +103,31 @@ fn test_make_and_take_with_litesvm() {
 
     let vault = get_associated_token_address(&escrow_pda, &mint_a);
 
+    // Per-maker counter of open escrows, created on the maker's first make
+    let (maker_state, _bump) = Pubkey::find_program_address(
+        &[b"maker_state", maker.pubkey().as_ref()],
+        &program_id,
+    );
+
+    // Registries listing open escrows per mint pair and per maker
+    let (pair_registry, _bump) = Pubkey::find_program_address(
+        &[b"pair_registry", mint_a.as_ref(), mint_b.as_ref()],
+        &program_id,
+    );
+    let (maker_registry, _bump) = Pubkey::find_program_address(
+        &[b"maker_registry", maker.pubkey().as_ref()],
+        &program_id,
+    );
+
+    // Program-wide pause switches; never initialized here, so nothing is paused
+    let (config, _bump) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+    // Admin allow/deny listings for both mints; unlisted mints are allowed
+    let (mint_a_listing, _bump) =
+        Pubkey::find_program_address(&[b"mint_listing", mint_a.as_ref()], &program_id);
+    let (mint_b_listing, _bump) =
+        Pubkey::find_program_address(&[b"mint_listing", mint_b.as_ref()], &program_id);
+
     // Build make instruction discriminator
     let mut hasher = Sha256::new();
     hasher.update(b"global:make");
@@ -121,6 +146,10 @@ fn test_make_and_take_with_litesvm() {
     make_instruction_data.extend_from_slice(&make_args.seed.to_le_bytes());
     make_instruction_data.extend_from_slice(&make_args.receive.to_le_bytes());
     make_instruction_data.extend_from_slice(&make_args.amount.to_le_bytes());
+    // Borsh encodes the (empty) extensions Vec as a u32 length prefix
+    make_instruction_data.extend_from_slice(&0u32.to_le_bytes());
+    // No hazardous mint features accepted (`accepted_hazards` bit flags)
+    make_instruction_data.push(0);
 
     // Build the make instruction
     // NOTE: ORDER MATTERS!! The accounts must be listed in the correct order for the transaction of the instruction to successfully execute 
@@ -136,6 +165,12 @@ fn test_make_and_take_with_litesvm() {
             AccountMeta::new_readonly(spl_associated_token_account::id(), false), // associated_token_program
             AccountMeta::new_readonly(spl_token::id(), false), // token_program
             AccountMeta::new_readonly(system_program::id(), false), // system_program
+            AccountMeta::new(maker_state, false),     // maker_state
+            AccountMeta::new(pair_registry, false),   // pair_registry
+            AccountMeta::new(maker_registry, false),  // maker_registry
+            AccountMeta::new_readonly(config, false), // config
+            AccountMeta::new_readonly(mint_a_listing, false), // mint_a_listing
+            AccountMeta::new_readonly(mint_b_listing, false), // mint_b_listing
         ],
         data: make_instruction_data,
     };
@@ -161,8 +196,10 @@ fn test_make_and_take_with_litesvm() {
     let mut take_discriminator = [0u8; 8];
     take_discriminator.copy_from_slice(&hash[..8]);
 
-    // Take instruction has no arguments, just the discriminator
-    let take_instruction_data = take_discriminator.to_vec();
+    // Take instruction data: discriminator + referral_bps (u16) + allowlist (None)
+    let mut take_instruction_data = take_discriminator.to_vec();
+    take_instruction_data.extend_from_slice(&0u16.to_le_bytes()); // referral_bps
+    take_instruction_data.push(0); // allowlist: None
 
     // Build the take instruction with all required accounts
     let take_instruction = Instruction {
@@ -180,6 +217,17 @@ fn test_make_and_take_with_litesvm() {
             AccountMeta::new_readonly(spl_associated_token_account::id(), false), // associated_token_program
             AccountMeta::new_readonly(spl_token::id(), false), // token_program
             AccountMeta::new_readonly(system_program::id(), false), // system_program
+            AccountMeta::new_readonly(program_id, false), // price_feed (optional: the program id stands in for None)
+            AccountMeta::new(maker_state, false),     // maker_state
+            AccountMeta::new(pair_registry, false),   // pair_registry
+            AccountMeta::new(maker_registry, false),  // maker_registry
+            AccountMeta::new_readonly(config, false), // config
+            AccountMeta::new_readonly(program_id, false), // memo_program (optional: None)
+            AccountMeta::new_readonly(program_id, false), // maker_destination_b (optional: None)
+            AccountMeta::new_readonly(program_id, false), // taker_destination_a (optional: None)
+            AccountMeta::new_readonly(program_id, false), // referrer_b (optional: None)
+            AccountMeta::new_readonly(program_id, false), // taker_gate (optional: None)
+            AccountMeta::new_readonly(program_id, false), // taker_fill (optional: None)
         ],
         data: take_instruction_data,
     };
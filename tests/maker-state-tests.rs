@@ -0,0 +1,137 @@
+use litesvm_token::spl_token;
+use solana_sdk::{signature::Signer, system_program};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+use anchor_escrow::client::{accounts, args};
+
+mod common;
+
+const MAX_OPEN_ESCROWS: u64 = 64;
+
+fn setup() -> common::Env {
+    common::setup(1_000_000, 0)
+}
+
+fn maker_state(env: &common::Env) -> anchor_escrow::accounts::MakerState {
+    env.ctx
+        .get_account(&common::maker_state(&env.maker.pubkey()))
+        .unwrap()
+}
+
+/// `make_next`, expecting the counter to be at `seed`
+fn make_next(env: &mut common::Env, seed: u64) -> litesvm_utils::TransactionResult {
+    let maker = env.maker.insecure_clone();
+    env.execute(
+        env.make_accounts(seed),
+        args::MakeNext { seed, receive: 10, amount: 10, extensions: vec![], accepted_hazards: 0 },
+        &maker,
+    )
+}
+
+fn make_zero_copy(env: &mut common::Env, seed: u64) -> litesvm_utils::TransactionResult {
+    let maker = env.maker.insecure_clone();
+    env.execute(
+        common::make_zero_copy_accounts(&maker.pubkey(), seed, &env.mint_a, &env.mint_b, &spl_token::id()),
+        args::MakeZeroCopy { seed, receive: 10, amount: 10, accepted_hazards: 0 },
+        &maker,
+    )
+}
+
+fn make_delegated(env: &mut common::Env, seed: u64) -> litesvm_utils::TransactionResult {
+    let maker = env.maker.insecure_clone();
+    env.execute(
+        common::make_delegated_accounts(&maker.pubkey(), seed, &env.mint_a, &env.mint_b, &spl_token::id()),
+        args::MakeDelegated { seed, receive: 10, amount: 10, accepted_hazards: 0 },
+        &maker,
+    )
+}
+
+#[test]
+fn test_make_next_assigns_sequential_seeds() {
+    let mut env = setup();
+
+    make_next(&mut env, 0).assert_success();
+    make_next(&mut env, 1).assert_success();
+
+    let state = maker_state(&env);
+    assert_eq!(state.maker, env.maker.pubkey());
+    assert_eq!(state.next_seed, 2);
+    assert_eq!(state.open_escrows, 2);
+
+    let escrow: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow(1)).unwrap();
+    assert_eq!(escrow.seed, 1);
+
+    // A client that read the counter before the last make is refused
+    make_next(&mut env, 1).assert_anchor_error("SeedMismatch");
+
+    // Refunding closes the escrow and frees a slot
+    let maker = env.maker.insecure_clone();
+    let make = env.make_accounts(0);
+    env.execute(
+        accounts::Refund {
+            maker: make.maker,
+            escrow: make.escrow,
+            mint_a: make.mint_a,
+            vault: make.vault,
+            maker_ata_a: make.maker_ata_a,
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: make.maker_state,
+            pair_registry: make.pair_registry,
+            maker_registry: make.maker_registry,
+        },
+        args::Refund {},
+        &maker,
+    )
+    .assert_success();
+    assert_eq!(maker_state(&env).open_escrows, 1);
+}
+
+#[test]
+fn test_explicit_seed_moves_counter_past_itself() {
+    let mut env = setup();
+
+    // An explicit make at the counter's next seed must not block make_next
+    env.make(0, 10, 10, vec![]).assert_success();
+    assert_eq!(maker_state(&env).next_seed, 1);
+    make_next(&mut env, 1).assert_success();
+
+    env.make(1_000, 10, 10, vec![]).assert_success();
+    // Seeds below the counter leave it where it is
+    env.make(5, 10, 10, vec![]).assert_success();
+    let state = maker_state(&env);
+    assert_eq!(state.next_seed, 1_001);
+    assert_eq!(state.open_escrows, 4);
+    make_next(&mut env, 1_001).assert_success();
+}
+
+#[test]
+fn test_open_escrows_are_capped() {
+    let mut env = setup();
+
+    for seed in 0..MAX_OPEN_ESCROWS {
+        make_next(&mut env, seed).assert_success();
+    }
+    make_next(&mut env, MAX_OPEN_ESCROWS).assert_anchor_error("TooManyOpenEscrows");
+}
+
+#[test]
+fn test_zero_copy_and_delegated_escrows_count_towards_cap() {
+    let mut env = setup();
+
+    // Delegated escrows share the `escrow` seeds and claim theirs; zero-copy
+    // escrows live under `escrow_zc` and leave the counter alone
+    make_delegated(&mut env, 0).assert_success();
+    make_zero_copy(&mut env, 0).assert_success();
+    let state = maker_state(&env);
+    assert_eq!(state.next_seed, 1);
+    assert_eq!(state.open_escrows, 2);
+
+    for seed in 1..MAX_OPEN_ESCROWS - 1 {
+        make_next(&mut env, seed).assert_success();
+    }
+    make_zero_copy(&mut env, 1).assert_anchor_error("TooManyOpenEscrows");
+    make_delegated(&mut env, MAX_OPEN_ESCROWS - 1).assert_anchor_error("TooManyOpenEscrows");
+}
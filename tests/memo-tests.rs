@@ -0,0 +1,104 @@
+mod common;
+
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{pubkey, pubkey::Pubkey, signature::Signer};
+use spl_token_2022::extension::{
+    memo_transfer::instruction::enable_required_transfer_memos, ExtensionType,
+};
+
+use common::{create_token_2022_ata, create_token_2022_mint, mint_token_2022, send, token_2022_ata};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+use anchor_escrow::types::{EscrowExtension, Memo};
+
+/// SPL Memo v3, which LiteSVM loads with the other SPL programs
+const MEMO_PROGRAM: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+const SEED: u64 = 7;
+
+/// Token-2022 escrow whose maker and taker both require memos on incoming
+/// transfers.
+fn setup(extensions: Vec<EscrowExtension>) -> common::Env {
+    let mut ctx = common::load_program();
+    assert!(ctx.svm.get_account(&MEMO_PROGRAM).unwrap().executable);
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = create_token_2022_mint(&mut ctx.svm, &maker, 6, &[], |_| vec![], None);
+    let mint_b = create_token_2022_mint(&mut ctx.svm, &maker, 6, &[], |_| vec![], None);
+
+    let maker_ata_a = create_token_2022_ata(&mut ctx.svm, &maker, &maker.pubkey(), &mint_a);
+    mint_token_2022(&mut ctx.svm, &maker, &mint_a, &maker_ata_a, 1_000);
+    let taker_ata_b = create_token_2022_ata(&mut ctx.svm, &taker, &taker.pubkey(), &mint_b);
+    mint_token_2022(&mut ctx.svm, &maker, &mint_b, &taker_ata_b, 1_000);
+
+    // Both payout accounts get the MemoTransfer extension turned on
+    for (owner, mint) in [(&maker, &mint_b), (&taker, &mint_a)] {
+        let ata = create_token_2022_ata(&mut ctx.svm, owner, &owner.pubkey(), mint);
+        let ixs = [
+            spl_token_2022::instruction::reallocate(
+                &spl_token_2022::id(),
+                &ata,
+                &owner.pubkey(),
+                &owner.pubkey(),
+                &[],
+                &[ExtensionType::MemoTransfer],
+            )
+            .unwrap(),
+            enable_required_transfer_memos(&spl_token_2022::id(), &ata, &owner.pubkey(), &[])
+                .unwrap(),
+        ];
+        send(&mut ctx.svm, &ixs, owner, &[]);
+    }
+
+    let mut env = common::Env {
+        ctx,
+        maker,
+        taker,
+        mint_a,
+        mint_b,
+        token_program: spl_token_2022::id(),
+    };
+    env.make(SEED, 500, 1_000, extensions).assert_success();
+    env
+}
+
+fn take(env: &mut common::Env, memo_program: Option<Pubkey>) -> litesvm_utils::TransactionResult {
+    let taker = env.taker.insecure_clone();
+    env.execute(
+        anchor_escrow::client::accounts::Take { memo_program, ..env.take_accounts(SEED) },
+        anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None },
+        &taker,
+    )
+}
+
+#[test]
+fn test_take_into_memo_required_accounts() {
+    let mut env = setup(vec![]);
+
+    // Without the memo program the first transfer into maker_ata_b is refused
+    take(&mut env, None)
+        .assert_failure()
+        .assert_log_error("No memo in previous instruction");
+
+    let result = take(&mut env, Some(MEMO_PROGRAM));
+    result.assert_success();
+    assert!(result.has_log("escrow 7"));
+
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.maker.pubkey(), &env.mint_b), 500);
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.taker.pubkey(), &env.mint_a), 1_000);
+    env.ctx.svm.assert_account_closed(&env.escrow(SEED));
+}
+
+#[test]
+fn test_take_memos_the_escrow_memo_extension() {
+    let mut env = setup(vec![EscrowExtension::Memo(Memo { memo: "invoice 1042".to_string() })]);
+
+    let result = take(&mut env, Some(MEMO_PROGRAM));
+    result.assert_success();
+    assert!(result.has_log("invoice 1042"));
+    assert!(!result.has_log("escrow 7"));
+}
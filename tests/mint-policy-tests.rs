@@ -0,0 +1,107 @@
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+use anchor_escrow::types::MintStatus;
+
+/// The usual fixture with the config created by the returned admin.
+fn setup() -> (common::Env, Keypair) {
+    let mut env = common::setup(1_000_000, 0);
+    let admin = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    common::initialize_config(&mut env.ctx.svm, &admin);
+    (env, admin)
+}
+
+fn set_listing(env: &mut common::Env, signer: &Keypair, mint: Pubkey, status: MintStatus) -> litesvm_utils::TransactionResult {
+    env.execute(
+        anchor_escrow::client::accounts::SetMintListing {
+            admin: signer.pubkey(),
+            config: common::config(),
+            mint,
+            mint_listing: common::mint_listing(&mint),
+            system_program: system_program::id(),
+        },
+        anchor_escrow::client::args::SetMintListing { status },
+        signer,
+    )
+}
+
+fn remove_listing(env: &mut common::Env, admin: &Keypair, mint: Pubkey) {
+    env.execute(
+        anchor_escrow::client::accounts::RemoveMintListing {
+            admin: admin.pubkey(),
+            config: common::config(),
+            mint_listing: common::mint_listing(&mint),
+        },
+        anchor_escrow::client::args::RemoveMintListing {},
+        admin,
+    )
+    .assert_success();
+}
+
+fn set_allowlist_only(env: &mut common::Env, admin: &Keypair, allowlist_only: bool) {
+    env.execute(
+        anchor_escrow::client::accounts::SetAllowlistOnly {
+            admin: admin.pubkey(),
+            config: common::config(),
+        },
+        anchor_escrow::client::args::SetAllowlistOnly { allowlist_only },
+        admin,
+    )
+    .assert_success();
+}
+
+fn make(env: &mut common::Env, seed: u64) -> litesvm_utils::TransactionResult {
+    env.make(seed, 100, 100, vec![])
+}
+
+#[test]
+fn test_denied_mint_is_rejected() {
+    let (mut env, admin) = setup();
+    let mint_b = env.mint_b;
+
+    set_listing(&mut env, &admin, mint_b, MintStatus::Denied).assert_success();
+    make(&mut env, 1).assert_anchor_error("MintNotAllowed");
+
+    // Removing the listing falls back to the open default
+    remove_listing(&mut env, &admin, mint_b);
+    env.ctx.svm.assert_account_closed(&common::mint_listing(&mint_b));
+    make(&mut env, 1).assert_success();
+}
+
+#[test]
+fn test_allowlist_requires_both_mints() {
+    let (mut env, admin) = setup();
+    let (mint_a, mint_b) = (env.mint_a, env.mint_b);
+
+    set_allowlist_only(&mut env, &admin, true);
+    make(&mut env, 1).assert_anchor_error("MintNotAllowed");
+
+    set_listing(&mut env, &admin, mint_a, MintStatus::Allowed).assert_success();
+    make(&mut env, 1).assert_anchor_error("MintNotAllowed");
+
+    set_listing(&mut env, &admin, mint_b, MintStatus::Allowed).assert_success();
+    make(&mut env, 1).assert_success();
+
+    // Denying overrides an earlier allow
+    set_listing(&mut env, &admin, mint_a, MintStatus::Denied).assert_success();
+    make(&mut env, 2).assert_anchor_error("MintNotAllowed");
+}
+
+#[test]
+fn test_only_admin_can_list_mints() {
+    let (mut env, _) = setup();
+    let maker = env.maker.insecure_clone();
+    let mint_b = env.mint_b;
+    set_listing(&mut env, &maker, mint_b, MintStatus::Denied)
+        .assert_anchor_error("Unauthorized");
+    make(&mut env, 1).assert_success();
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use common::{
+    create_token_2022_ata, create_token_2022_mint, create_token_2022_nft, mint_token_2022,
+    token_2022_ata,
+};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+use anchor_escrow::types::TakeQuote;
+
+struct Env {
+    ctx: AnchorContext,
+    maker: Keypair,
+    taker: Keypair,
+    mint_b: Pubkey,
+    /// Royalty creators with 70% and 30% shares
+    creators: [Pubkey; 2],
+}
+
+fn setup() -> Env {
+    let mut ctx = common::load_program();
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_b = create_token_2022_mint(&mut ctx.svm, &maker, 6, &[], |_| vec![], None);
+    let taker_ata_b = create_token_2022_ata(&mut ctx.svm, &taker, &taker.pubkey(), &mint_b);
+    mint_token_2022(&mut ctx.svm, &maker, &mint_b, &taker_ata_b, 100_000);
+
+    let creators = [Pubkey::new_unique(), Pubkey::new_unique()];
+    for creator in &creators {
+        create_token_2022_ata(&mut ctx.svm, &maker, creator, &mint_b);
+    }
+
+    Env { ctx, maker, taker, mint_b, creators }
+}
+
+impl Env {
+    /// An NFT paying 5% royalties, 70/30 to `creators`
+    fn royalty_nft(&mut self) -> Pubkey {
+        let maker = self.maker.insecure_clone();
+        let fields = [
+            ("royalty_basis_points".to_string(), "500".to_string()),
+            (self.creators[0].to_string(), "70".to_string()),
+            (self.creators[1].to_string(), "30".to_string()),
+        ];
+        create_token_2022_nft(&mut self.ctx.svm, &maker, &fields, None)
+    }
+
+    fn make_nft(&mut self, seed: u64, nft: Pubkey, receive: u64) -> litesvm_utils::TransactionResult {
+        let maker = self.maker.insecure_clone();
+        let ix = self.ctx.program()
+            .accounts(common::make_accounts(&maker.pubkey(), seed, &nft, &self.mint_b, &spl_token_2022::id()))
+            .args(anchor_escrow::client::args::MakeNft {
+                seed,
+                receive,
+                collection: None,
+                accepted_hazards: 0,
+            })
+            .instruction()
+            .unwrap();
+        self.ctx.execute_instruction(ix, &[&maker]).unwrap()
+    }
+
+    fn take(&mut self, seed: u64, nft: Pubkey, creator_accounts: &[Pubkey]) -> litesvm_utils::TransactionResult {
+        let taker = self.taker.insecure_clone();
+        let mut ix = self.ctx.program()
+            .accounts(common::take_accounts(
+                &taker.pubkey(),
+                &self.maker.pubkey(),
+                &common::escrow(&self.maker.pubkey(), seed),
+                &nft,
+                &self.mint_b,
+                &spl_token_2022::id(),
+            ))
+            .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+            .instruction()
+            .unwrap();
+        ix.accounts.extend(creator_accounts.iter().map(|&a| AccountMeta::new(a, false)));
+        self.ctx.execute_instruction(ix, &[&taker]).unwrap()
+    }
+
+    fn quote(&mut self, seed: u64, nft: Pubkey) -> TakeQuote {
+        let escrow = common::escrow(&self.maker.pubkey(), seed);
+        let ix = self.ctx.program()
+            .accounts(anchor_escrow::client::accounts::Quote {
+                escrow,
+                mint_a: nft,
+                mint_b: self.mint_b,
+                vault: token_2022_ata(&escrow, &nft),
+                token_program: spl_token_2022::id(),
+                price_feed: None,
+                config: common::config(),
+            })
+            .args(anchor_escrow::client::args::Quote { amount: None, referral_bps: 0 })
+            .instruction()
+            .unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.taker.pubkey()),
+            &[&self.taker],
+            self.ctx.svm.latest_blockhash(),
+        );
+        let simulated = self.ctx.svm.simulate_transaction(tx).unwrap();
+        TakeQuote::try_from_slice(&simulated.meta.return_data.data).unwrap()
+    }
+}
+
+#[test]
+fn test_take_pays_creator_royalties_from_proceeds() {
+    let mut env = setup();
+    let nft = env.royalty_nft();
+    env.make_nft(1, nft, 10_000).assert_success();
+
+    // Quotes leave the royalty out of what the maker receives
+    let quote = env.quote(1, nft);
+    assert_eq!(quote.amount_b_paid, 10_000);
+    assert_eq!(quote.royalty_b, 500);
+    assert_eq!(quote.amount_b_received, 9_500);
+
+    let creator_atas = env.creators.map(|c| token_2022_ata(&c, &env.mint_b));
+    env.take(1, nft, &creator_atas).assert_success();
+
+    // The taker pays the price; 5% of it goes to the creators, 70/30
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.taker.pubkey(), &env.mint_b), 90_000);
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.maker.pubkey(), &env.mint_b), 9_500);
+    env.ctx.svm.assert_token_balance(&creator_atas[0], 350);
+    env.ctx.svm.assert_token_balance(&creator_atas[1], 150);
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.taker.pubkey(), &nft), 1);
+}
+
+#[test]
+fn test_royalty_accounts_must_belong_to_creators() {
+    let mut env = setup();
+    let nft = env.royalty_nft();
+    env.make_nft(1, nft, 10_000).assert_success();
+    let creator_atas = env.creators.map(|c| token_2022_ata(&c, &env.mint_b));
+    let taker_ata_b = token_2022_ata(&env.taker.pubkey(), &env.mint_b);
+
+    env.take(1, nft, &creator_atas[..1])
+        .assert_anchor_error("MissingTokenAccount");
+    env.take(1, nft, &[creator_atas[1], creator_atas[0]])
+        .assert_anchor_error("InvalidRoyaltyRecipient");
+    env.take(1, nft, &[creator_atas[0], taker_ata_b])
+        .assert_anchor_error("InvalidRoyaltyRecipient");
+}
+
+#[test]
+fn test_nft_without_royalties_pays_maker_in_full() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let nft = create_token_2022_nft(&mut env.ctx.svm, &maker, &[], None);
+    env.make_nft(1, nft, 10_000).assert_success();
+
+    env.take(1, nft, &[]).assert_success();
+    env.ctx.svm.assert_token_balance(&token_2022_ata(&env.maker.pubkey(), &env.mint_b), 10_000);
+}
+
+#[test]
+fn test_make_nft_refuses_fungible_mints() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+
+    // Decimals other than 0
+    let fungible = create_token_2022_mint(&mut env.ctx.svm, &maker, 6, &[], |_| vec![], None);
+    let ata = create_token_2022_ata(&mut env.ctx.svm, &maker, &maker.pubkey(), &fungible);
+    mint_token_2022(&mut env.ctx.svm, &maker, &fungible, &ata, 1);
+    env.make_nft(1, fungible, 10_000).assert_anchor_error("NotAnNft");
+
+    // A second token minted after the fact
+    let nft = env.royalty_nft();
+    let ata = token_2022_ata(&maker.pubkey(), &nft);
+    mint_token_2022(&mut env.ctx.svm, &maker, &nft, &ata, 1);
+    env.make_nft(1, nft, 10_000).assert_anchor_error("NotAnNft");
+}
+
+#[test]
+fn test_plain_make_refuses_nfts() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let nft = env.royalty_nft();
+
+    // Sold through `make`, the NFT would skip its creators' royalties
+    let ix = env.ctx.program()
+        .accounts(common::make_accounts(&maker.pubkey(), 1, &nft, &env.mint_b, &spl_token_2022::id()))
+        .args(anchor_escrow::client::args::Make {
+            seed: 1,
+            receive: 10_000,
+            amount: 1,
+            extensions: vec![],
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_anchor_error("NftNeedsMakeNft");
+}
+
+#[test]
+fn test_make_nft_requires_metadata_for_spl_token_mints() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+
+    // SPL Token mints carry no royalties of their own
+    let nft = env.ctx.svm.create_token_mint(&maker, 0).unwrap().pubkey();
+    let mint_b = env.ctx.svm.create_token_mint(&maker, 6).unwrap().pubkey();
+    let ata = env.ctx.svm.create_associated_token_account(&nft, &maker).unwrap();
+    env.ctx.svm.mint_to(&nft, &ata, &maker, 1).unwrap();
+
+    let ix = env.ctx.program()
+        .accounts(common::make_accounts(&maker.pubkey(), 1, &nft, &mint_b, &litesvm_token::spl_token::id()))
+        .args(anchor_escrow::client::args::MakeNft {
+            seed: 1,
+            receive: 10_000,
+            collection: None,
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_anchor_error("MissingNftMetadata");
+}
@@ -0,0 +1,312 @@
+use anchor_litesvm::{AnchorContext, AnchorLiteSVM};
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+use anchor_escrow::types::OracleConfig;
+
+/// Must match `declare_id!` in programs/mock_oracle
+const MOCK_ORACLE_ID: Pubkey = pubkey!("6r2E4hFibeJbkFagfqJMh7vvqhj45edf3HKTLpen2P2s");
+/// Pyth legacy price account size
+const PRICE_ACCOUNT_LEN: u64 = 3312;
+const STATUS_TRADING: u32 = 1;
+const STATUS_HALTED: u32 = 2;
+const NOW: i64 = 1_700_000_000;
+
+struct OracleEnv {
+    ctx: AnchorContext,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    price_feed: Keypair,
+    escrow_pda: Pubkey,
+    taker_ata_b: Pubkey,
+}
+
+/// mock_oracle has no IDL in this repo, so its instruction is built by hand
+fn set_price_ix(
+    price_feed: &Pubkey,
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+    status: u32,
+) -> Instruction {
+    let mut hasher = Sha256::new();
+    hasher.update(b"global:set_price");
+    let mut data = hasher.finalize()[..8].to_vec();
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&conf.to_le_bytes());
+    data.extend_from_slice(&expo.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&status.to_le_bytes());
+
+    Instruction {
+        program_id: MOCK_ORACLE_ID,
+        accounts: vec![AccountMeta::new(*price_feed, false)],
+        data,
+    }
+}
+
+/// Offers all of the maker's 1.0 mint_a at `oracle`'s price
+fn make_oracle_ix(
+    ctx: &AnchorContext,
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    seed: u64,
+    oracle: OracleConfig,
+) -> Instruction {
+    ctx.program()
+        .accounts(common::make_accounts(maker, seed, mint_a, mint_b, &spl_token::id()))
+        .args(anchor_escrow::client::args::MakeOracle {
+            seed,
+            amount: 1_000_000_000,
+            oracle,
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap()
+}
+
+/// mint_a has 9 decimals, mint_b has 6; the maker offers 1.0 mint_a
+fn setup() -> OracleEnv {
+    let mut ctx = AnchorLiteSVM::build_with_programs(&[
+        (anchor_escrow::ID, include_bytes!("../target/deploy/anchor_escrow.so")),
+        (MOCK_ORACLE_ID, include_bytes!("../target/deploy/mock_oracle.so")),
+    ]);
+
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = NOW;
+    ctx.svm.set_sysvar(&clock);
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = ctx.svm.create_token_mint(&maker, 9).unwrap();
+    let mint_b = ctx.svm.create_token_mint(&maker, 6).unwrap();
+
+    let maker_ata_a = ctx.svm
+        .create_associated_token_account(&mint_a.pubkey(), &maker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_a.pubkey(), &maker_ata_a, &maker, 1_000_000_000)
+        .unwrap(); // 1.0 mint_a
+
+    let taker_ata_b = ctx.svm
+        .create_associated_token_account(&mint_b.pubkey(), &taker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_b.pubkey(), &taker_ata_b, &maker, 10_000_000)
+        .unwrap(); // 10.0 mint_b
+
+    // Raw price account owned by the mock oracle, like a Pyth price account
+    let price_feed = Keypair::new();
+    let rent = ctx.svm.minimum_balance_for_rent_exemption(PRICE_ACCOUNT_LEN as usize);
+    let create_ix = system_instruction::create_account(
+        &maker.pubkey(),
+        &price_feed.pubkey(),
+        rent,
+        PRICE_ACCOUNT_LEN,
+        &MOCK_ORACLE_ID,
+    );
+    ctx.execute_instruction(create_ix, &[&maker, &price_feed])
+        .unwrap()
+        .assert_success();
+
+    let seed: u64 = 11;
+    let escrow_pda = common::escrow(&maker.pubkey(), seed);
+
+    let make_ix = make_oracle_ix(
+        &ctx,
+        &maker.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+        seed,
+        OracleConfig {
+            price_feed: price_feed.pubkey(),
+            spread_bps: 100,         // 1% over the oracle
+            max_staleness: 60,       // seconds
+            max_confidence_bps: 50,  // 0.5% of price
+        },
+    );
+    ctx.execute_instruction(make_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    OracleEnv {
+        ctx,
+        maker,
+        taker,
+        mint_a: mint_a.pubkey(),
+        mint_b: mint_b.pubkey(),
+        price_feed,
+        escrow_pda,
+        taker_ata_b,
+    }
+}
+
+fn publish(env: &mut OracleEnv, price: i64, conf: u64, expo: i32, publish_time: i64) {
+    publish_with_status(env, price, conf, expo, publish_time, STATUS_TRADING);
+}
+
+fn publish_with_status(
+    env: &mut OracleEnv,
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+    status: u32,
+) {
+    let ix = set_price_ix(&env.price_feed.pubkey(), price, conf, expo, publish_time, status);
+    let maker = env.maker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_success();
+}
+
+fn take_ix(env: &OracleEnv, price_feed: Option<Pubkey>) -> Instruction {
+    env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::Take {
+            price_feed,
+            ..common::take_accounts(
+                &env.taker.pubkey(),
+                &env.maker.pubkey(),
+                &env.escrow_pda,
+                &env.mint_a,
+                &env.mint_b,
+                &spl_token::id(),
+            )
+        })
+        .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+        .instruction()
+        .unwrap()
+}
+
+#[test]
+fn test_take_at_oracle_price_with_spread() {
+    let mut env = setup();
+    // 1 mint_a = 2.5 mint_b, conf 0.1%
+    publish(&mut env, 250_000_000, 250_000, -8, NOW - 10);
+
+    let ix = take_ix(&env, Some(env.price_feed.pubkey()));
+    let taker = env.taker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_success();
+
+    // 1.0 mint_a * 2.5 * 1.01 spread = 2.525 mint_b (6 decimals)
+    env.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&env.maker.pubkey(), &env.mint_b),
+        2_525_000,
+    );
+    env.ctx.svm.assert_token_balance(&env.taker_ata_b, 10_000_000 - 2_525_000);
+    env.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&env.taker.pubkey(), &env.mint_a),
+        1_000_000_000,
+    );
+    env.ctx.svm.assert_account_closed(&env.escrow_pda);
+}
+
+#[test]
+fn test_take_rejects_stale_price() {
+    let mut env = setup();
+    publish(&mut env, 250_000_000, 250_000, -8, NOW - 61);
+
+    let ix = take_ix(&env, Some(env.price_feed.pubkey()));
+    let taker = env.taker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("StalePrice");
+}
+
+#[test]
+fn test_take_rejects_wide_confidence() {
+    let mut env = setup();
+    // conf of 1% exceeds the maker's 0.5% limit
+    publish(&mut env, 250_000_000, 2_500_000, -8, NOW);
+
+    let ix = take_ix(&env, Some(env.price_feed.pubkey()));
+    let taker = env.taker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("PriceConfidenceTooWide");
+}
+
+#[test]
+fn test_take_rejects_price_that_is_not_trading() {
+    let mut env = setup();
+    publish_with_status(&mut env, 250_000_000, 250_000, -8, NOW, STATUS_HALTED);
+
+    let ix = take_ix(&env, Some(env.price_feed.pubkey()));
+    let taker = env.taker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("OraclePriceNotTrading");
+}
+
+#[test]
+fn test_take_rejects_non_positive_price() {
+    let mut env = setup();
+    publish(&mut env, 0, 0, -8, NOW);
+
+    let ix = take_ix(&env, Some(env.price_feed.pubkey()));
+    let taker = env.taker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("InvalidOraclePrice");
+}
+
+#[test]
+fn test_take_requires_price_feed() {
+    let mut env = setup();
+    publish(&mut env, 250_000_000, 250_000, -8, NOW);
+
+    let ix = take_ix(&env, None);
+    let taker = env.taker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("InvalidPriceFeed");
+
+    // So is any account other than the feed recorded at make time
+    let ix = take_ix(&env, Some(env.taker_ata_b));
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("InvalidPriceFeed");
+}
+
+#[test]
+fn test_make_rejects_invalid_oracle_config() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let config = |max_staleness, max_confidence_bps| OracleConfig {
+        price_feed: env.price_feed.pubkey(),
+        spread_bps: 0,
+        max_staleness,
+        max_confidence_bps,
+    };
+
+    let ix = make_oracle_ix(&env.ctx, &maker.pubkey(), &env.mint_a, &env.mint_b, 12, config(0, 50));
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_anchor_error("InvalidOracleStaleness");
+    let ix = make_oracle_ix(&env.ctx, &maker.pubkey(), &env.mint_a, &env.mint_b, 12, config(60, 0));
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_anchor_error("InvalidOracleConfidence");
+}
@@ -0,0 +1,169 @@
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+/// The usual fixture with the config created by the returned admin.
+fn setup() -> (common::Env, Keypair) {
+    let mut env = common::setup(1_000_000, 1_000_000);
+    let admin = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    common::initialize_config(&mut env.ctx.svm, &admin);
+    (env, admin)
+}
+
+fn config(env: &common::Env) -> anchor_escrow::accounts::Config {
+    env.ctx.get_account(&common::config()).unwrap()
+}
+
+fn set_pause(env: &mut common::Env, signer: &Keypair, make_paused: bool, take_paused: bool) -> litesvm_utils::TransactionResult {
+    env.execute(
+        anchor_escrow::client::accounts::SetPause {
+            admin: signer.pubkey(),
+            config: common::config(),
+        },
+        anchor_escrow::client::args::SetPause { make_paused, take_paused },
+        signer,
+    )
+}
+
+fn make(env: &mut common::Env, seed: u64) -> litesvm_utils::TransactionResult {
+    env.make(seed, 100, 100, vec![])
+}
+
+fn refund_ix(env: &common::Env, seed: u64) -> Instruction {
+    let escrow = env.escrow(seed);
+    env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::Refund {
+            maker: env.maker.pubkey(),
+            escrow,
+            mint_a: env.mint_a,
+            vault: get_associated_token_address(&escrow, &env.mint_a),
+            maker_ata_a: get_associated_token_address(&env.maker.pubkey(), &env.mint_a),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: common::maker_state(&env.maker.pubkey()),
+            pair_registry: common::pair_registry(&env.mint_a, &env.mint_b),
+            maker_registry: common::maker_registry(&env.maker.pubkey()),
+        })
+        .args(anchor_escrow::client::args::Refund {})
+        .instruction()
+        .unwrap()
+}
+
+#[test]
+fn test_paused_make_fails_but_refund_succeeds() {
+    let (mut env, admin) = setup();
+    make(&mut env, 1).assert_success();
+
+    set_pause(&mut env, &admin, true, true).assert_success();
+    assert!(config(&env).make_paused);
+
+    make(&mut env, 2).assert_anchor_error("MakePaused");
+
+    // Makers can always get their deposit back, even with everything paused
+    let ix = refund_ix(&env, 1);
+    let maker = env.maker.insecure_clone();
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_success();
+    env.ctx.svm.assert_account_closed(&env.escrow(1));
+
+    set_pause(&mut env, &admin, false, true).assert_success();
+    make(&mut env, 2).assert_success();
+}
+
+#[test]
+fn test_paused_take_fails_until_resumed() {
+    let (mut env, admin) = setup();
+    make(&mut env, 1).assert_success();
+
+    set_pause(&mut env, &admin, false, true).assert_success();
+    env.take(1).assert_anchor_error("TakePaused");
+
+    set_pause(&mut env, &admin, false, false).assert_success();
+    env.take(1).assert_success();
+    env.ctx.svm.assert_account_closed(&env.escrow(1));
+}
+
+#[test]
+fn test_only_admin_can_pause() {
+    let (mut env, _) = setup();
+    let maker = env.maker.insecure_clone();
+    set_pause(&mut env, &maker, true, true).assert_anchor_error("Unauthorized");
+    assert!(!config(&env).make_paused);
+}
+
+#[test]
+fn test_only_upgrade_authority_can_initialize_config() {
+    let mut ctx = common::load_program();
+    let authority = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let frontrunner = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    common::set_upgrade_authority(&mut ctx.svm, &authority.pubkey());
+
+    // Racing the deployer to the config gains nothing
+    ctx.execute_instruction(common::initialize_config_ix(&frontrunner.pubkey()), &[&frontrunner])
+        .unwrap()
+        .assert_anchor_error("Unauthorized");
+    ctx.execute_instruction(common::initialize_config_ix(&authority.pubkey()), &[&authority])
+        .unwrap()
+        .assert_success();
+}
+
+#[test]
+fn test_admin_rotation_needs_acceptance() {
+    let (mut env, admin) = setup();
+    let new_admin = env.ctx.svm.create_funded_account(1_000_000_000).unwrap();
+
+    let ix = env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::ProposeAdmin {
+            admin: admin.pubkey(),
+            config: common::config(),
+        })
+        .args(anchor_escrow::client::args::ProposeAdmin { new_admin: new_admin.pubkey() })
+        .instruction()
+        .unwrap();
+    env.ctx.execute_instruction(ix, &[&admin])
+        .unwrap()
+        .assert_success();
+
+    // Proposing alone changes nothing
+    assert_eq!(config(&env).admin, admin.pubkey());
+    assert_eq!(config(&env).pending_admin, Some(new_admin.pubkey()));
+
+    let accept_ix = |signer: &Keypair| {
+        env.ctx.program()
+            .accounts(anchor_escrow::client::accounts::AcceptAdmin {
+                new_admin: signer.pubkey(),
+                config: common::config(),
+            })
+            .args(anchor_escrow::client::args::AcceptAdmin {})
+            .instruction()
+            .unwrap()
+    };
+    let maker = env.maker.insecure_clone();
+    let wrong = accept_ix(&maker);
+    let right = accept_ix(&new_admin);
+
+    env.ctx.execute_instruction(wrong, &[&maker])
+        .unwrap()
+        .assert_anchor_error("NotPendingAdmin");
+    env.ctx.execute_instruction(right, &[&new_admin])
+        .unwrap()
+        .assert_success();
+
+    assert_eq!(config(&env).admin, new_admin.pubkey());
+    assert_eq!(config(&env).pending_admin, None);
+
+    set_pause(&mut env, &admin, true, false).assert_anchor_error("Unauthorized");
+    set_pause(&mut env, &new_admin, true, false).assert_success();
+}
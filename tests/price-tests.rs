@@ -0,0 +1,151 @@
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+struct PricedEscrow {
+    ctx: AnchorContext,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    escrow_pda: Pubkey,
+    vault: Pubkey,
+}
+
+/// Offers 1.0 mint_a (9 decimals) at 2.5 mint_b (6 decimals) each
+fn setup() -> PricedEscrow {
+    let mut ctx = common::load_program();
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = ctx.svm.create_token_mint(&maker, 9).unwrap();
+    let mint_b = ctx.svm.create_token_mint(&maker, 6).unwrap();
+
+    let maker_ata_a = ctx.svm
+        .create_associated_token_account(&mint_a.pubkey(), &maker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_a.pubkey(), &maker_ata_a, &maker, 1_000_000_000)
+        .unwrap();
+
+    let taker_ata_b = ctx.svm
+        .create_associated_token_account(&mint_b.pubkey(), &taker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_b.pubkey(), &taker_ata_b, &maker, 10_000_000)
+        .unwrap();
+
+    let seed: u64 = 3;
+    let escrow_pda = common::escrow(&maker.pubkey(), seed);
+    let vault = get_associated_token_address(&escrow_pda, &mint_a.pubkey());
+
+    // The ask is given in whole tokens; the program normalises decimals
+    let make_ix = ctx.program()
+        .accounts(common::make_accounts(&maker.pubkey(), seed, &mint_a.pubkey(), &mint_b.pubkey(), &spl_token::id()))
+        .args(anchor_escrow::client::args::MakePriced {
+            seed,
+            amount: 1_000_000_000,
+            price_numerator: 5,   // 5 mint_b...
+            price_denominator: 2, // ...per 2 mint_a
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap();
+    ctx.execute_instruction(make_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    PricedEscrow {
+        ctx,
+        maker,
+        taker,
+        mint_a: mint_a.pubkey(),
+        mint_b: mint_b.pubkey(),
+        escrow_pda,
+        vault,
+    }
+}
+
+fn take_partial_ix(env: &PricedEscrow, amount: u64) -> Instruction {
+    env.ctx.program()
+        .accounts(common::take_accounts(
+            &env.taker.pubkey(),
+            &env.maker.pubkey(),
+            &env.escrow_pda,
+            &env.mint_a,
+            &env.mint_b,
+            &spl_token::id(),
+        ))
+        .args(anchor_escrow::client::args::TakePartial { amount, referral_bps: 0, allowlist: None })
+        .instruction()
+        .unwrap()
+}
+
+#[test]
+fn test_make_priced_normalises_decimals() {
+    let env = setup();
+
+    let escrow: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow_pda).unwrap();
+    // 5 * 10^6 / (2 * 10^9) reduced
+    assert_eq!(escrow.price.numerator, 1);
+    assert_eq!(escrow.price.denominator, 400);
+    assert_eq!(escrow.receive, 2_500_000);
+    env.ctx.svm.assert_token_balance(&env.vault, 1_000_000_000);
+}
+
+#[test]
+fn test_partial_takes_until_drained() {
+    let mut env = setup();
+    let taker = env.taker.insecure_clone();
+    let taker_ata_a = get_associated_token_address(&env.taker.pubkey(), &env.mint_a);
+    let maker_ata_b = get_associated_token_address(&env.maker.pubkey(), &env.mint_b);
+
+    // 0.4 mint_a costs exactly 1.0 mint_b
+    let ix = take_partial_ix(&env, 400_000_000);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_success();
+    env.ctx.svm.assert_token_balance(&taker_ata_a, 400_000_000);
+    env.ctx.svm.assert_token_balance(&maker_ata_b, 1_000_000);
+    env.ctx.svm.assert_token_balance(&env.vault, 600_000_000);
+    env.ctx.svm.assert_account_exists(&env.escrow_pda);
+    let escrow: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow_pda).unwrap();
+    assert!(matches!(escrow.status, anchor_escrow::types::EscrowStatus::PartiallyFilled));
+    assert_eq!(escrow.fill_count, 1);
+    assert!(escrow.updated_at >= escrow.created_at);
+
+    // A single base unit of mint_a is worth 1/400 of a mint_b unit: rounds up to 1
+    let ix = take_partial_ix(&env, 1);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_success();
+    env.ctx.svm.assert_token_balance(&maker_ata_b, 1_000_001);
+
+    // Asking for more than is left fails
+    let ix = take_partial_ix(&env, 600_000_000);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("InvalidAmount");
+
+    // The rest drains the vault and closes the escrow
+    let ix = take_partial_ix(&env, 599_999_999);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_success();
+    env.ctx.svm.assert_token_balance(&taker_ata_a, 1_000_000_000);
+    env.ctx.svm.assert_token_balance(&maker_ata_b, 2_500_001);
+    env.ctx.svm.assert_account_closed(&env.vault);
+    env.ctx.svm.assert_account_closed(&env.escrow_pda);
+}
@@ -0,0 +1,105 @@
+use litesvm_token::CreateAccount;
+use litesvm_utils::AssertionHelpers;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account::get_associated_token_address;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+use anchor_escrow::types::{EscrowExtension, ProceedsDestination};
+
+const SEED: u64 = 11;
+
+/// An escrow of 1_000 mint_a for 500 mint_b paid out to the returned
+/// treasury account: a non-ATA mint_b account not owned by the maker.
+fn setup() -> (common::Env, Pubkey) {
+    let mut env = common::setup(1_000, 1_000);
+    let treasury_b = CreateAccount::new(&mut env.ctx.svm, &env.maker, &env.mint_b)
+        .owner(&Pubkey::new_unique())
+        .send()
+        .unwrap();
+
+    let extensions = vec![EscrowExtension::ProceedsDestination(ProceedsDestination {
+        token_account: treasury_b,
+    })];
+    env.make(SEED, 500, 1_000, extensions).assert_success();
+    (env, treasury_b)
+}
+
+fn take(
+    env: &mut common::Env,
+    maker_ata_b: Option<Pubkey>,
+    maker_destination_b: Option<Pubkey>,
+    taker_destination_a: Option<Pubkey>,
+) -> litesvm_utils::TransactionResult {
+    let taker = env.taker.insecure_clone();
+    // The taker's ATA is only needed when no other destination is given
+    let taker_ata_a = taker_destination_a
+        .is_none()
+        .then(|| get_associated_token_address(&taker.pubkey(), &env.mint_a));
+    env.execute(
+        anchor_escrow::client::accounts::Take {
+            taker_ata_a,
+            maker_ata_b,
+            maker_destination_b,
+            taker_destination_a,
+            ..env.take_accounts(SEED)
+        },
+        anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None },
+        &taker,
+    )
+}
+
+#[test]
+fn test_proceeds_go_to_recorded_destination() {
+    let (mut env, treasury_b) = setup();
+    let taker = env.taker.insecure_clone();
+    let taker_cold_a = CreateAccount::new(&mut env.ctx.svm, &taker, &env.mint_a)
+        .owner(&taker.pubkey())
+        .send()
+        .unwrap();
+
+    take(&mut env, None, Some(treasury_b), Some(taker_cold_a))
+        .assert_success();
+
+    env.ctx.svm.assert_token_balance(&treasury_b, 500);
+    env.ctx.svm.assert_token_balance(&taker_cold_a, 1_000);
+    // Neither side's ATA was created at the taker's expense
+    let maker_ata_b = get_associated_token_address(&env.maker.pubkey(), &env.mint_b);
+    let taker_ata_a = get_associated_token_address(&taker.pubkey(), &env.mint_a);
+    assert!(env.ctx.svm.get_account(&maker_ata_b).is_none());
+    assert!(env.ctx.svm.get_account(&taker_ata_a).is_none());
+}
+
+#[test]
+fn test_proceeds_cannot_be_redirected() {
+    let (mut env, treasury_b) = setup();
+    let maker_ata_b = get_associated_token_address(&env.maker.pubkey(), &env.mint_b);
+    let taker_ata_b = get_associated_token_address(&env.taker.pubkey(), &env.mint_b);
+
+    // The maker's ATA is not the recorded destination
+    take(&mut env, Some(maker_ata_b), None, None)
+        .assert_anchor_error("InvalidProceedsDestination");
+    // Nor is any other mint_b account, such as the taker's own
+    take(&mut env, None, Some(taker_ata_b), None)
+        .assert_anchor_error("InvalidProceedsDestination");
+
+    take(&mut env, None, Some(treasury_b), None)
+        .assert_success();
+    env.ctx.svm.assert_token_balance(&treasury_b, 500);
+}
+
+#[test]
+fn test_taker_destination_must_be_owned_by_taker() {
+    let (mut env, treasury_b) = setup();
+    let maker = env.maker.insecure_clone();
+    let someone_else_a = CreateAccount::new(&mut env.ctx.svm, &maker, &env.mint_a)
+        .owner(&Pubkey::new_unique())
+        .send()
+        .unwrap();
+
+    take(&mut env, None, Some(treasury_b), Some(someone_else_a))
+        .assert_anchor_error("ConstraintTokenOwner");
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::extension::{transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType};
+
+use common::{create_token_2022_ata, create_token_2022_mint, mint_token_2022, token_2022_ata};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+use anchor_escrow::types::TakeQuote;
+
+#[test]
+fn test_quote_matches_take_with_transfer_fees() {
+    let mut ctx = common::load_program();
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    // ============================================================================
+    // Token-2022 mints charging 0.5% (mint_a) and 1% (mint_b) on every transfer
+    // ============================================================================
+    let with_fee = |bps: u16| {
+        let authority = maker.pubkey();
+        move |mint: &solana_sdk::pubkey::Pubkey| {
+            vec![initialize_transfer_fee_config(
+                &spl_token_2022::id(),
+                mint,
+                Some(&authority),
+                Some(&authority),
+                bps,
+                u64::MAX,
+            )
+            .unwrap()]
+        }
+    };
+    let mint_a = create_token_2022_mint(
+        &mut ctx.svm,
+        &maker,
+        9,
+        &[ExtensionType::TransferFeeConfig],
+        with_fee(50),
+        None,
+    );
+    let mint_b = create_token_2022_mint(
+        &mut ctx.svm,
+        &maker,
+        6,
+        &[ExtensionType::TransferFeeConfig],
+        with_fee(100),
+        None,
+    );
+
+    let maker_ata_a = create_token_2022_ata(&mut ctx.svm, &maker, &maker.pubkey(), &mint_a);
+    mint_token_2022(&mut ctx.svm, &maker, &mint_a, &maker_ata_a, 1_000_000_000);
+    let taker_ata_b = create_token_2022_ata(&mut ctx.svm, &taker, &taker.pubkey(), &mint_b);
+    mint_token_2022(&mut ctx.svm, &maker, &mint_b, &taker_ata_b, 10_000_000);
+
+    let seed: u64 = 5;
+    let escrow_pda = common::escrow(&maker.pubkey(), seed);
+    let vault = token_2022_ata(&escrow_pda, &mint_a);
+
+    let make_ix = ctx.program()
+        .accounts(common::make_accounts(&maker.pubkey(), seed, &mint_a, &mint_b, &spl_token_2022::id()))
+        .args(anchor_escrow::client::args::Make {
+            seed,
+            receive: 2_000_000,
+            amount: 1_000_000_000,
+            extensions: vec![],
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap();
+    ctx.execute_instruction(make_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    // The deposit itself paid 0.5%, so the vault holds 0.995 mint_a
+    ctx.svm.assert_token_balance(&vault, 995_000_000);
+
+    // ============================================================================
+    // Simulate "quote" and decode the return data
+    // ============================================================================
+    let quote_ix = ctx.program()
+        .accounts(anchor_escrow::client::accounts::Quote {
+            escrow: escrow_pda,
+            mint_a,
+            mint_b,
+            vault,
+            token_program: spl_token_2022::id(),
+            price_feed: None,
+            config: common::config(),
+        })
+        .args(anchor_escrow::client::args::Quote { amount: None, referral_bps: 0 })
+        .instruction()
+        .unwrap();
+    let quote = simulate_quote(&mut ctx.svm, quote_ix, &taker);
+
+    assert_eq!(quote.amount_a, 995_000_000);
+    assert_eq!(quote.transfer_fee_a, 4_975_000);
+    assert_eq!(quote.amount_a_received, 990_025_000);
+    assert_eq!(quote.amount_b, 1_990_000); // 0.995 * 2.0 mint_b per mint_a
+    assert_eq!(quote.fee_b, 0);
+    assert_eq!(quote.transfer_fee_b, 19_900);
+    assert_eq!(quote.amount_b_paid, 1_990_000);
+    assert_eq!(quote.amount_b_received, 1_970_100);
+
+    // ============================================================================
+    // The real take lands exactly on the quoted numbers
+    // ============================================================================
+    let taker_ata_a = token_2022_ata(&taker.pubkey(), &mint_a);
+    let maker_ata_b = token_2022_ata(&maker.pubkey(), &mint_b);
+    let take_ix = ctx.program()
+        .accounts(common::take_accounts(
+            &taker.pubkey(),
+            &maker.pubkey(),
+            &escrow_pda,
+            &mint_a,
+            &mint_b,
+            &spl_token_2022::id(),
+        ))
+        .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+        .instruction()
+        .unwrap();
+    ctx.execute_instruction(take_ix, &[&taker])
+        .unwrap()
+        .assert_success();
+
+    ctx.svm.assert_token_balance(&taker_ata_a, quote.amount_a_received);
+    ctx.svm.assert_token_balance(&maker_ata_b, quote.amount_b_received);
+    ctx.svm.assert_token_balance(&taker_ata_b, 10_000_000 - quote.amount_b_paid);
+}
+
+fn simulate_quote(
+    svm: &mut litesvm::LiteSVM,
+    quote_ix: Instruction,
+    payer: &solana_sdk::signature::Keypair,
+) -> TakeQuote {
+    let tx = Transaction::new_signed_with_payer(
+        &[quote_ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        svm.latest_blockhash(),
+    );
+    let simulated = svm.simulate_transaction(tx).unwrap();
+    TakeQuote::try_from_slice(&simulated.meta.return_data.data).unwrap()
+}
@@ -0,0 +1,146 @@
+use anchor_lang::AnchorDeserialize;
+use litesvm_token::CreateAccount;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+use anchor_escrow::types::TakeQuote;
+
+mod common;
+
+const SEED: u64 = 5;
+
+/// An escrow of 1_000 mint_a for 1_000 mint_b, with an admin-run config and
+/// the front-end's mint_b account.
+fn setup() -> (common::Env, Keypair, Pubkey) {
+    let mut env = common::setup(1_000, 2_000);
+    let admin = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let referrer_b = CreateAccount::new(&mut env.ctx.svm, &env.maker, &env.mint_b)
+        .owner(&Pubkey::new_unique())
+        .send()
+        .unwrap();
+
+    common::initialize_config(&mut env.ctx.svm, &admin);
+    env.make(SEED, 1_000, 1_000, vec![]).assert_success();
+    (env, admin, referrer_b)
+}
+
+fn set_referral_cap(env: &mut common::Env, signer: &Keypair, max_referral_bps: u16) -> litesvm_utils::TransactionResult {
+    env.execute(
+        anchor_escrow::client::accounts::SetReferralCap {
+            admin: signer.pubkey(),
+            config: common::config(),
+        },
+        anchor_escrow::client::args::SetReferralCap { max_referral_bps },
+        signer,
+    )
+}
+
+fn take(env: &mut common::Env, referrer_b: Option<Pubkey>, referral_bps: u16) -> litesvm_utils::TransactionResult {
+    let taker = env.taker.insecure_clone();
+    env.execute(
+        anchor_escrow::client::accounts::Take { referrer_b, ..env.take_accounts(SEED) },
+        anchor_escrow::client::args::Take { referral_bps, allowlist: None },
+        &taker,
+    )
+}
+
+#[test]
+fn test_referral_fee_is_paid_by_taker() {
+    let (mut env, admin, referrer_b) = setup();
+    set_referral_cap(&mut env, &admin, 100).assert_success();
+
+    take(&mut env, Some(referrer_b), 50).assert_success();
+
+    // The maker still receives `receive`; the taker pays the 0.5% on top
+    let maker_ata_b = get_associated_token_address(&env.maker.pubkey(), &env.mint_b);
+    let taker_ata_b = get_associated_token_address(&env.taker.pubkey(), &env.mint_b);
+    env.ctx.svm.assert_token_balance(&maker_ata_b, 1_000);
+    env.ctx.svm.assert_token_balance(&referrer_b, 5);
+    env.ctx.svm.assert_token_balance(&taker_ata_b, 995);
+}
+
+/// Simulates `quote` for a full take paying `referral_bps`.
+fn quote(env: &mut common::Env, referral_bps: u16) -> TakeQuote {
+    let take = env.take_accounts(SEED);
+    let ix = env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::Quote {
+            escrow: take.escrow,
+            mint_a: take.mint_a,
+            mint_b: take.mint_b,
+            vault: take.vault,
+            token_program: take.token_program,
+            price_feed: None,
+            config: common::config(),
+        })
+        .args(anchor_escrow::client::args::Quote { amount: None, referral_bps })
+        .instruction()
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&env.taker.pubkey()),
+        &[&env.taker],
+        env.ctx.svm.latest_blockhash(),
+    );
+    let simulated = env.ctx.svm.simulate_transaction(tx).unwrap();
+    TakeQuote::try_from_slice(&simulated.meta.return_data.data).unwrap()
+}
+
+#[test]
+fn test_quote_includes_referral_fee() {
+    let (mut env, admin, referrer_b) = setup();
+    set_referral_cap(&mut env, &admin, 100).assert_success();
+
+    let quote = quote(&mut env, 50);
+    assert_eq!(quote.amount_b, 1_000);
+    assert_eq!(quote.fee_b, 5);
+    assert_eq!(quote.amount_b_paid, 1_005);
+    assert_eq!(quote.amount_b_received, 1_000);
+
+    take(&mut env, Some(referrer_b), 50).assert_success();
+    let taker_ata_b = get_associated_token_address(&env.taker.pubkey(), &env.mint_b);
+    env.ctx.svm.assert_token_balance(&referrer_b, quote.fee_b);
+    env.ctx.svm.assert_token_balance(&taker_ata_b, 2_000 - quote.amount_b_paid);
+}
+
+#[test]
+fn test_referral_fee_is_capped() {
+    let (mut env, admin, referrer_b) = setup();
+
+    // Referral fees are off until the admin sets a cap
+    take(&mut env, Some(referrer_b), 1).assert_anchor_error("ReferralFeeTooHigh");
+
+    set_referral_cap(&mut env, &admin, 100).assert_success();
+    take(&mut env, Some(referrer_b), 101).assert_anchor_error("ReferralFeeTooHigh");
+    set_referral_cap(&mut env, &admin, 10_001).assert_anchor_error("ReferralFeeTooHigh");
+
+    take(&mut env, Some(referrer_b), 100).assert_success();
+    env.ctx.svm.assert_token_balance(&referrer_b, 10);
+}
+
+#[test]
+fn test_referrer_account_is_validated() {
+    let (mut env, admin, _) = setup();
+    set_referral_cap(&mut env, &admin, 100).assert_success();
+
+    take(&mut env, None, 50).assert_anchor_error("MissingTokenAccount");
+    // A mint_a account cannot collect a mint_b fee
+    let maker_ata_a = get_associated_token_address(&env.maker.pubkey(), &env.mint_a);
+    take(&mut env, Some(maker_ata_a), 50).assert_anchor_error("ConstraintTokenMint");
+
+    // Without a fee the referrer account is optional
+    take(&mut env, None, 0).assert_success();
+}
+
+#[test]
+fn test_only_admin_sets_referral_cap() {
+    let (mut env, ..) = setup();
+    let maker = env.maker.insecure_clone();
+    set_referral_cap(&mut env, &maker, 1_000).assert_anchor_error("Unauthorized");
+}
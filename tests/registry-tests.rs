@@ -0,0 +1,226 @@
+use litesvm_utils::TestHelpers;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+/// Entries a fresh registry has room for, and the step it grows by
+const GROWTH: usize = 8;
+const MAX_ENTRIES: usize = 256;
+const MAX_OPEN_ESCROWS: usize = 64;
+
+/// The usual fixture plus a third mint, mint_c, to make a second pair with.
+fn setup() -> (common::Env, Pubkey) {
+    let mut env = common::setup(1_000_000, 1_000_000);
+    let mint_c = env.ctx.svm.create_token_mint(&env.maker, 6).unwrap().pubkey();
+    (env, mint_c)
+}
+
+fn registry(env: &common::Env, address: &Pubkey) -> Vec<Pubkey> {
+    let registry: anchor_escrow::accounts::Registry = env.ctx.get_account(address).unwrap();
+    registry.escrows
+}
+
+fn make(env: &mut common::Env, seed: u64, mint_b: Pubkey) -> Pubkey {
+    let maker = env.maker.insecure_clone();
+    make_as(env, &maker, seed, mint_b)
+}
+
+fn make_as(env: &mut common::Env, maker: &Keypair, seed: u64, mint_b: Pubkey) -> Pubkey {
+    env.execute(
+        common::make_accounts(&maker.pubkey(), seed, &env.mint_a, &mint_b, &spl_token::id()),
+        anchor_escrow::client::args::Make {
+            seed,
+            receive: 10,
+            amount: 10,
+            extensions: vec![],
+            accepted_hazards: 0,
+        },
+        maker,
+    )
+    .assert_success();
+    common::escrow(&maker.pubkey(), seed)
+}
+
+fn refund(env: &mut common::Env, seed: u64, mint_b: Pubkey) {
+    let escrow = env.escrow(seed);
+    let maker = env.maker.insecure_clone();
+    env.execute(
+        anchor_escrow::client::accounts::Refund {
+            maker: maker.pubkey(),
+            escrow,
+            mint_a: env.mint_a,
+            vault: get_associated_token_address(&escrow, &env.mint_a),
+            maker_ata_a: get_associated_token_address(&maker.pubkey(), &env.mint_a),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: common::maker_state(&maker.pubkey()),
+            pair_registry: common::pair_registry(&env.mint_a, &mint_b),
+            maker_registry: common::maker_registry(&maker.pubkey()),
+        },
+        anchor_escrow::client::args::Refund {},
+        &maker,
+    )
+    .assert_success();
+}
+
+#[test]
+fn test_make_inserts_into_both_registries() {
+    let (mut env, mint_c) = setup();
+    let (mint_a, mint_b) = (env.mint_a, env.mint_b);
+
+    let first = make(&mut env, 1, mint_b);
+    let second = make(&mut env, 2, mint_b);
+    let other_pair = make(&mut env, 3, mint_c);
+
+    assert_eq!(registry(&env, &common::pair_registry(&mint_a, &mint_b)), vec![first, second]);
+    assert_eq!(registry(&env, &common::pair_registry(&mint_a, &mint_c)), vec![other_pair]);
+    assert_eq!(
+        registry(&env, &common::maker_registry(&env.maker.pubkey())),
+        vec![first, second, other_pair]
+    );
+}
+
+#[test]
+fn test_take_and_refund_remove_entries() {
+    let (mut env, mint_c) = setup();
+    let (mint_a, mint_b) = (env.mint_a, env.mint_b);
+    let pair = common::pair_registry(&mint_a, &mint_b);
+    let maker = common::maker_registry(&env.maker.pubkey());
+
+    let first = make(&mut env, 1, mint_b);
+    let second = make(&mut env, 2, mint_b);
+    let third = make(&mut env, 3, mint_c);
+
+    env.take(1).assert_success();
+    assert_eq!(registry(&env, &pair), vec![second]);
+    // The last entry is swapped into the gap
+    assert_eq!(registry(&env, &maker), vec![third, second]);
+    assert!(!registry(&env, &maker).contains(&first));
+
+    refund(&mut env, 3, mint_c);
+    assert!(registry(&env, &common::pair_registry(&mint_a, &mint_c)).is_empty());
+    assert_eq!(registry(&env, &maker), vec![second]);
+}
+
+#[test]
+fn test_registry_grows_with_capacity() {
+    let (mut env, _) = setup();
+    let mint_b = env.mint_b;
+    let pair = common::pair_registry(&env.mint_a, &mint_b);
+    let space = |capacity: usize| 1 + 1 + 4 + 32 * capacity;
+
+    make(&mut env, 0, mint_b);
+    assert_eq!(env.ctx.svm.get_account(&pair).unwrap().data.len(), space(GROWTH));
+
+    for seed in 1..GROWTH as u64 {
+        make(&mut env, seed, mint_b);
+    }
+    assert_eq!(env.ctx.svm.get_account(&pair).unwrap().data.len(), space(GROWTH));
+
+    // One past the initial capacity reallocates by another step
+    make(&mut env, GROWTH as u64, mint_b);
+    assert_eq!(env.ctx.svm.get_account(&pair).unwrap().data.len(), space(2 * GROWTH));
+    assert_eq!(registry(&env, &pair).len(), GROWTH + 1);
+}
+
+#[test]
+fn test_full_pair_registry_does_not_block_makes() {
+    let (mut env, _) = setup();
+    let (mint_a, mint_b) = (env.mint_a, env.mint_b);
+    let pair = common::pair_registry(&mint_a, &mint_b);
+
+    // Each maker is capped at MAX_OPEN_ESCROWS, so fill the pair from several
+    let mut makers = vec![];
+    for _ in 0..MAX_ENTRIES / MAX_OPEN_ESCROWS + 1 {
+        let maker = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+        let ata = env.ctx.svm.create_associated_token_account(&mint_a, &maker).unwrap();
+        let mint_authority = env.maker.insecure_clone();
+        env.ctx.svm.mint_to(&mint_a, &ata, &mint_authority, 1_000_000).unwrap();
+        makers.push(maker);
+    }
+    for maker in &makers[..MAX_ENTRIES / MAX_OPEN_ESCROWS] {
+        for seed in 0..MAX_OPEN_ESCROWS as u64 {
+            make_as(&mut env, maker, seed, mint_b);
+        }
+    }
+    assert_eq!(registry(&env, &pair).len(), MAX_ENTRIES);
+
+    // The next escrow still opens; it is only left out of the full pair listing
+    let late = makers.last().unwrap();
+    let unlisted = make_as(&mut env, late, 0, mint_b);
+    assert_eq!(registry(&env, &pair).len(), MAX_ENTRIES);
+    assert!(!registry(&env, &pair).contains(&unlisted));
+    assert_eq!(registry(&env, &common::maker_registry(&late.pubkey())), vec![unlisted]);
+}
+
+#[test]
+fn test_zero_copy_and_delegated_escrows_are_listed() {
+    let (mut env, _) = setup();
+    let (mint_a, mint_b) = (env.mint_a, env.mint_b);
+    let maker = env.maker.insecure_clone();
+    let pair = common::pair_registry(&mint_a, &mint_b);
+    let maker_registry = common::maker_registry(&maker.pubkey());
+
+    env.execute(
+        common::make_zero_copy_accounts(&maker.pubkey(), 1, &mint_a, &mint_b, &spl_token::id()),
+        anchor_escrow::client::args::MakeZeroCopy { seed: 1, receive: 10, amount: 10, accepted_hazards: 0 },
+        &maker,
+    )
+    .assert_success();
+    env.execute(
+        common::make_delegated_accounts(&maker.pubkey(), 2, &mint_a, &mint_b, &spl_token::id()),
+        anchor_escrow::client::args::MakeDelegated { seed: 2, receive: 10, amount: 10, accepted_hazards: 0 },
+        &maker,
+    )
+    .assert_success();
+    let zero_copy = common::escrow_zero_copy(&maker.pubkey(), 1);
+    let delegated = env.escrow(2);
+    assert_eq!(registry(&env, &pair), vec![zero_copy, delegated]);
+    assert_eq!(registry(&env, &maker_registry), vec![zero_copy, delegated]);
+
+    env.execute(
+        anchor_escrow::client::accounts::RefundZeroCopy {
+            maker: maker.pubkey(),
+            escrow: zero_copy,
+            mint_a,
+            vault: get_associated_token_address(&zero_copy, &mint_a),
+            maker_ata_a: get_associated_token_address(&maker.pubkey(), &mint_a),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: common::maker_state(&maker.pubkey()),
+            pair_registry: pair,
+            maker_registry,
+        },
+        anchor_escrow::client::args::RefundZeroCopy {},
+        &maker,
+    )
+    .assert_success();
+    env.execute(
+        anchor_escrow::client::accounts::CancelDelegated {
+            maker: maker.pubkey(),
+            escrow: delegated,
+            mint_a,
+            maker_ata_a: get_associated_token_address(&maker.pubkey(), &mint_a),
+            token_program: spl_token::id(),
+            maker_state: common::maker_state(&maker.pubkey()),
+            pair_registry: pair,
+            maker_registry,
+        },
+        anchor_escrow::client::args::CancelDelegated {},
+        &maker,
+    )
+    .assert_success();
+    assert!(registry(&env, &pair).is_empty());
+    assert!(registry(&env, &maker_registry).is_empty());
+}
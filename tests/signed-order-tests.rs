@@ -0,0 +1,296 @@
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+use anchor_escrow::types::{MintStatus, SignedOrder};
+
+const NOW: i64 = 1_700_000_000;
+
+struct SignedOrderEnv {
+    ctx: AnchorContext,
+    program_id: Pubkey,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    maker_ata_a: Pubkey,
+    taker_ata_b: Pubkey,
+}
+
+fn setup() -> SignedOrderEnv {
+    let program_id = anchor_escrow::ID;
+    let mut ctx = common::load_program();
+
+    // Pin the clock so expiry checks are deterministic
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = NOW;
+    ctx.svm.set_sysvar(&clock);
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = ctx.svm.create_token_mint(&maker, 9).unwrap();
+    let mint_b = ctx.svm.create_token_mint(&maker, 9).unwrap();
+
+    let maker_ata_a = ctx.svm
+        .create_associated_token_account(&mint_a.pubkey(), &maker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_a.pubkey(), &maker_ata_a, &maker, 1_000_000_000)
+        .unwrap();
+
+    let taker_ata_b = ctx.svm
+        .create_associated_token_account(&mint_b.pubkey(), &taker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_b.pubkey(), &taker_ata_b, &maker, 500_000_000)
+        .unwrap();
+
+    // One-time on-chain step for the maker: approve the order authority PDA.
+    // Every signed order after this is created entirely off-chain.
+    let order_authority = ctx.svm.get_pda(
+        &[b"order_authority", maker.pubkey().as_ref()],
+        &program_id,
+    );
+    let approve_ix = spl_token::instruction::approve(
+        &spl_token::id(),
+        &maker_ata_a,
+        &order_authority,
+        &maker.pubkey(),
+        &[],
+        1_000_000_000,
+    )
+    .unwrap();
+    ctx.execute_instruction(approve_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    SignedOrderEnv {
+        ctx,
+        program_id,
+        maker,
+        taker,
+        mint_a: mint_a.pubkey(),
+        mint_b: mint_b.pubkey(),
+        maker_ata_a,
+        taker_ata_b,
+    }
+}
+
+fn order(env: &SignedOrderEnv, nonce: u64, expiry: i64) -> SignedOrder {
+    SignedOrder {
+        maker: env.maker.pubkey(),
+        mint_a: env.mint_a,
+        mint_b: env.mint_b,
+        amount: 400_000_000,
+        receive: 200_000_000,
+        nonce,
+        expiry,
+    }
+}
+
+/// Mirrors `SignedOrder::message` in the program: program id || borsh(order)
+fn order_message(program_id: &Pubkey, order: &SignedOrder) -> Vec<u8> {
+    let mut message = program_id.to_bytes().to_vec();
+    message.extend_from_slice(&anchor_lang::prelude::borsh::to_vec(order).unwrap());
+    message
+}
+
+/// Builds the [ed25519 precompile, fill_signed_order] instruction pair.
+/// `signed` is what the maker actually signed, `submitted` what the taker sends.
+fn fill_ixs(env: &SignedOrderEnv, signed: &SignedOrder, submitted: SignedOrder) -> Vec<Instruction> {
+    let message = order_message(&env.program_id, signed);
+    let signature = env.maker.sign_message(&message);
+    let ed25519_ix = solana_ed25519_program::new_ed25519_instruction_with_signature(
+        &message,
+        signature.as_ref().try_into().unwrap(),
+        &env.maker.pubkey().to_bytes(),
+    );
+
+    let order_authority = env.ctx.svm.get_pda(
+        &[b"order_authority", env.maker.pubkey().as_ref()],
+        &env.program_id,
+    );
+    let order_nonce = env.ctx.svm.get_pda(
+        &[b"order_nonce", env.maker.pubkey().as_ref(), &submitted.nonce.to_le_bytes()],
+        &env.program_id,
+    );
+
+    let fill_ix = env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::FillSignedOrder {
+            taker: env.taker.pubkey(),
+            maker: env.maker.pubkey(),
+            order_authority,
+            order_nonce,
+            mint_a: env.mint_a,
+            mint_b: env.mint_b,
+            maker_ata_a: env.maker_ata_a,
+            taker_ata_a: get_associated_token_address(&env.taker.pubkey(), &env.mint_a),
+            taker_ata_b: env.taker_ata_b,
+            maker_ata_b: get_associated_token_address(&env.maker.pubkey(), &env.mint_b),
+            instructions: sysvar::instructions::id(),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            config: common::config(),
+            mint_a_listing: common::mint_listing(&env.mint_a),
+            mint_b_listing: common::mint_listing(&env.mint_b),
+        })
+        .args(anchor_escrow::client::args::FillSignedOrder { order: submitted, accepted_hazards: 0 })
+        .instruction()
+        .unwrap();
+
+    vec![ed25519_ix, fill_ix]
+}
+
+#[test]
+fn test_fill_signed_order() {
+    let mut env = setup();
+    let signed = order(&env, 1, NOW + 60);
+    let ixs = fill_ixs(&env, &signed, signed);
+
+    let taker = env.taker.insecure_clone();
+    let result = env.ctx.execute_instructions(ixs, &[&taker]).unwrap();
+    result.assert_success();
+    println!("fill_signed_order compute units: {}", result.compute_units());
+
+    env.ctx.svm.assert_token_balance(&env.maker_ata_a, 600_000_000);
+    env.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&env.taker.pubkey(), &env.mint_a),
+        400_000_000,
+    );
+    env.ctx.svm.assert_token_balance(&env.taker_ata_b, 300_000_000);
+    env.ctx.svm.assert_token_balance(
+        &get_associated_token_address(&env.maker.pubkey(), &env.mint_b),
+        200_000_000,
+    );
+}
+
+#[test]
+fn test_fill_signed_order_rejects_replay() {
+    let mut env = setup();
+    let signed = order(&env, 2, NOW + 60);
+    let taker = env.taker.insecure_clone();
+
+    let ixs = fill_ixs(&env, &signed, signed);
+    env.ctx.execute_instructions(ixs, &[&taker])
+        .unwrap()
+        .assert_success();
+
+    // Same signed payload again, in a fresh transaction
+    env.ctx.svm.expire_blockhash();
+    let ixs = fill_ixs(&env, &signed, signed);
+    env.ctx.execute_instructions(ixs, &[&taker])
+        .unwrap()
+        .assert_anchor_error("OrderAlreadyFilled");
+
+    env.ctx.svm.assert_token_balance(&env.maker_ata_a, 600_000_000);
+}
+
+#[test]
+fn test_fill_signed_order_rejects_expired() {
+    let mut env = setup();
+    let signed = order(&env, 3, NOW - 1);
+    let taker = env.taker.insecure_clone();
+
+    let ixs = fill_ixs(&env, &signed, signed);
+    env.ctx.execute_instructions(ixs, &[&taker])
+        .unwrap()
+        .assert_anchor_error("OrderExpired");
+
+    env.ctx.svm.assert_token_balance(&env.maker_ata_a, 1_000_000_000);
+}
+
+#[test]
+fn test_fill_signed_order_rejects_tampered_terms() {
+    let mut env = setup();
+    let signed = order(&env, 4, NOW + 60);
+    let taker = env.taker.insecure_clone();
+
+    // Taker tries to pay less than the maker signed for
+    let mut tampered = signed;
+    tampered.receive = 1;
+    let ixs = fill_ixs(&env, &signed, tampered);
+    env.ctx.execute_instructions(ixs, &[&taker])
+        .unwrap()
+        .assert_anchor_error("InvalidSignature");
+
+    env.ctx.svm.assert_token_balance(&env.taker_ata_b, 500_000_000);
+}
+
+#[test]
+fn test_fill_signed_order_requires_precompile() {
+    let mut env = setup();
+    let signed = order(&env, 5, NOW + 60);
+    let taker = env.taker.insecure_clone();
+
+    // Drop the ed25519 instruction entirely
+    let ixs = fill_ixs(&env, &signed, signed);
+    env.ctx.execute_instructions(ixs[1..].to_vec(), &[&taker])
+        .unwrap()
+        .assert_anchor_error("InvalidSignature");
+}
+
+#[test]
+fn test_fill_signed_order_respects_make_pause_and_mint_listings() {
+    let mut env = setup();
+    let admin = env.ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    common::initialize_config(&mut env.ctx.svm, &admin);
+    let taker = env.taker.insecure_clone();
+
+    let set_pause = |env: &mut SignedOrderEnv, make_paused: bool| {
+        let ix = env.ctx.program()
+            .accounts(anchor_escrow::client::accounts::SetPause {
+                admin: admin.pubkey(),
+                config: common::config(),
+            })
+            .args(anchor_escrow::client::args::SetPause { make_paused, take_paused: false })
+            .instruction()
+            .unwrap();
+        common::send(&mut env.ctx.svm, &[ix], &admin, &[]);
+    };
+
+    // A fill opens the order as well as taking it, so a make pause stops it
+    set_pause(&mut env, true);
+    let signed = order(&env, 6, NOW + 60);
+    let ixs = fill_ixs(&env, &signed, signed);
+    env.ctx.execute_instructions(ixs, &[&taker])
+        .unwrap()
+        .assert_anchor_error("MakePaused");
+    set_pause(&mut env, false);
+
+    // So does a denied mint
+    let ix = env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::SetMintListing {
+            admin: admin.pubkey(),
+            config: common::config(),
+            mint: env.mint_a,
+            mint_listing: common::mint_listing(&env.mint_a),
+            system_program: system_program::id(),
+        })
+        .args(anchor_escrow::client::args::SetMintListing { status: MintStatus::Denied })
+        .instruction()
+        .unwrap();
+    common::send(&mut env.ctx.svm, &[ix], &admin, &[]);
+    env.ctx.svm.expire_blockhash();
+    let ixs = fill_ixs(&env, &signed, signed);
+    env.ctx.execute_instructions(ixs, &[&taker])
+        .unwrap()
+        .assert_anchor_error("MintNotAllowed");
+
+    env.ctx.svm.assert_token_balance(&env.maker_ata_a, 1_000_000_000);
+}
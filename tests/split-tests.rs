@@ -0,0 +1,125 @@
+use litesvm_token::{spl_token, CreateAccount};
+use litesvm_utils::AssertionHelpers;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signature::Signer};
+use spl_associated_token_account::get_associated_token_address;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+use anchor_escrow::types::{EscrowExtension, ProceedsSplit, SplitRecipient};
+
+/// The usual fixture plus non-ATA mint_b accounts for three syndicate
+/// members.
+fn setup() -> (common::Env, Vec<Pubkey>) {
+    let mut env = common::setup(1_000, 1_000);
+    let recipients = (0..3)
+        .map(|_| {
+            CreateAccount::new(&mut env.ctx.svm, &env.maker, &env.mint_b)
+                .owner(&Pubkey::new_unique())
+                .send()
+                .unwrap()
+        })
+        .collect();
+    (env, recipients)
+}
+
+fn make(env: &mut common::Env, recipients: &[Pubkey], seed: u64, receive: u64, bps: &[u16]) -> litesvm_utils::TransactionResult {
+    let split = ProceedsSplit {
+        recipients: recipients
+            .iter()
+            .zip(bps)
+            .map(|(&token_account, &bps)| SplitRecipient { token_account, bps })
+            .collect(),
+    };
+    env.make(seed, receive, 1_000, vec![EscrowExtension::ProceedsSplit(split)])
+}
+
+/// Takes `amount` (or everything) with `split_accounts` as the remaining
+/// accounts.
+fn take(env: &mut common::Env, seed: u64, amount: Option<u64>, split_accounts: &[Pubkey]) -> litesvm_utils::TransactionResult {
+    let taker = env.taker.insecure_clone();
+    let program = env.ctx.program().accounts(anchor_escrow::client::accounts::Take {
+        maker_ata_b: None,
+        ..env.take_accounts(seed)
+    });
+    let mut ix = match amount {
+        Some(amount) => program.args(anchor_escrow::client::args::TakePartial { amount, referral_bps: 0, allowlist: None }),
+        None => program.args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None }),
+    }
+    .instruction()
+    .unwrap();
+    ix.accounts.extend(split_accounts.iter().map(|&a| AccountMeta::new(a, false)));
+    env.ctx.execute_instruction(ix, &[&taker]).unwrap()
+}
+
+fn balance(env: &common::Env, account: &Pubkey) -> u64 {
+    litesvm_token::get_spl_account::<spl_token::state::Account>(&env.ctx.svm, account)
+        .unwrap()
+        .amount
+}
+
+fn recipient_balances(env: &common::Env, recipients: &[Pubkey]) -> Vec<u64> {
+    recipients.iter().map(|r| balance(env, r)).collect()
+}
+
+#[test]
+fn test_take_splits_payment_and_conserves_sum() {
+    let (mut env, recipients) = setup();
+    make(&mut env, &recipients, 1, 1_000, &[3_333, 3_333, 3_334]).assert_success();
+
+    take(&mut env, 1, None, &recipients).assert_success();
+
+    // 333 + 333 + 333 leaves 1, which goes to the first recipient
+    assert_eq!(recipient_balances(&env, &recipients), vec![334, 333, 333]);
+    assert_eq!(recipient_balances(&env, &recipients).iter().sum::<u64>(), 1_000);
+    let taker_ata_b = get_associated_token_address(&env.taker.pubkey(), &env.mint_b);
+    env.ctx.svm.assert_token_balance(&taker_ata_b, 0);
+    // No payment went to the maker's ATA
+    let maker_ata_b = get_associated_token_address(&env.maker.pubkey(), &env.mint_b);
+    assert!(env.ctx.svm.get_account(&maker_ata_b).is_none());
+}
+
+#[test]
+fn test_partial_takes_conserve_each_payment() {
+    let (mut env, recipients) = setup();
+    // 1 mint_a pays 0.7 mint_b, rounded up for the taker
+    make(&mut env, &recipients, 1, 700, &[5_000, 2_500, 2_500]).assert_success();
+    let taker_ata_b = get_associated_token_address(&env.taker.pubkey(), &env.mint_b);
+
+    let mut paid = 0;
+    for amount in [3, 7, 990] {
+        let before = balance(&env, &taker_ata_b);
+        take(&mut env, 1, Some(amount), &recipients).assert_success();
+        paid += before - balance(&env, &taker_ata_b);
+        assert_eq!(recipient_balances(&env, &recipients).iter().sum::<u64>(), paid);
+    }
+}
+
+#[test]
+fn test_split_accounts_must_match_in_order() {
+    let (mut env, recipients) = setup();
+    make(&mut env, &recipients, 1, 1_000, &[5_000, 3_000, 2_000]).assert_success();
+
+    take(&mut env, 1, None, &recipients[..2])
+        .assert_anchor_error("MissingTokenAccount");
+    take(&mut env, 1, None, &[recipients[1], recipients[0], recipients[2]])
+        .assert_anchor_error("InvalidSplitRecipient");
+    let taker_ata_b = get_associated_token_address(&env.taker.pubkey(), &env.mint_b);
+    take(&mut env, 1, None, &[recipients[0], recipients[1], taker_ata_b])
+        .assert_anchor_error("InvalidSplitRecipient");
+
+    take(&mut env, 1, None, &recipients).assert_success();
+    assert_eq!(recipient_balances(&env, &recipients), vec![500, 300, 200]);
+}
+
+#[test]
+fn test_split_must_cover_10k_bps() {
+    let (mut env, recipients) = setup();
+    make(&mut env, &recipients, 1, 1_000, &[5_000, 3_000, 1_999])
+        .assert_anchor_error("InvalidProceedsSplit");
+    make(&mut env, &recipients, 1, 1_000, &[10_000, 0])
+        .assert_anchor_error("InvalidProceedsSplit");
+    make(&mut env, &recipients, 1, 1_000, &[10_000]).assert_success();
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use litesvm_utils::TestHelpers;
+use solana_sdk::{
+    clock::Clock,
+    signature::Signer,
+    transaction::Transaction,
+};
+use spl_token_2022::extension::{interest_bearing_mint, ExtensionType};
+
+use common::{create_token_2022_ata, create_token_2022_mint, mint_token_2022, token_2022_ata};
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+use anchor_escrow::types::TakeQuote;
+
+const NOW: i64 = 1_700_000_000;
+/// Token-2022 accrues interest over 365.24-day years
+const ONE_YEAR: i64 = 31_556_736;
+
+#[test]
+fn test_make_with_receive_in_interest_bearing_ui_units() {
+    let mut ctx = common::load_program();
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = NOW;
+    ctx.svm.set_sysvar(&clock);
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    // mint_b pays 5% a year, compounded continuously
+    let mint_a = create_token_2022_mint(&mut ctx.svm, &maker, 6, &[], |_| vec![], None);
+    let mint_b = create_token_2022_mint(
+        &mut ctx.svm,
+        &maker,
+        6,
+        &[ExtensionType::InterestBearingConfig],
+        |mint| {
+            vec![interest_bearing_mint::instruction::initialize(
+                &spl_token_2022::id(),
+                mint,
+                None,
+                500,
+            )
+            .unwrap()]
+        },
+        None,
+    );
+    let maker_ata_a = create_token_2022_ata(&mut ctx.svm, &maker, &maker.pubkey(), &mint_a);
+    mint_token_2022(&mut ctx.svm, &maker, &mint_a, &maker_ata_a, 1_000_000);
+
+    // A year on, 1 raw mint_b displays as e^0.05 ≈ 1.051271
+    clock.unix_timestamp = NOW + ONE_YEAR;
+    ctx.svm.set_sysvar(&clock);
+
+    let seed: u64 = 3;
+    let escrow = common::escrow(&maker.pubkey(), seed);
+    let vault = token_2022_ata(&escrow, &mint_a);
+    let make_ix = ctx.program()
+        .accounts(common::make_accounts(&maker.pubkey(), seed, &mint_a, &mint_b, &spl_token_2022::id()))
+        .args(anchor_escrow::client::args::MakeUiAmount {
+            seed,
+            receive_ui: 1_051_271,
+            amount: 1_000_000,
+            accepted_hazards: 0,
+        })
+        .instruction()
+        .unwrap();
+    ctx.execute_instruction(make_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    let state: anchor_escrow::accounts::Escrow = ctx.get_account(&escrow).unwrap();
+    assert_eq!(state.receive, 1_000_000);
+
+    // The entered amount and conversion time sit in the trailing `UiReceive`
+    // extension: type 4, length 16, receive_ui, converted_at
+    let data = ctx.svm.get_account(&escrow).unwrap().data;
+    let tlv = &data[data.len() - 20..];
+    assert_eq!(u16::from_le_bytes([tlv[0], tlv[1]]), 4);
+    assert_eq!(u16::from_le_bytes([tlv[2], tlv[3]]), 16);
+    assert_eq!(u64::from_le_bytes(tlv[4..12].try_into().unwrap()), 1_051_271);
+    assert_eq!(i64::from_le_bytes(tlv[12..20].try_into().unwrap()), NOW + ONE_YEAR);
+
+    // Quotes report both raw and UI amounts
+    let quote_ix = ctx.program()
+        .accounts(anchor_escrow::client::accounts::Quote {
+            escrow,
+            mint_a,
+            mint_b,
+            vault,
+            token_program: spl_token_2022::id(),
+            price_feed: None,
+            config: common::config(),
+        })
+        .args(anchor_escrow::client::args::Quote { amount: None, referral_bps: 0 })
+        .instruction()
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[quote_ix],
+        Some(&maker.pubkey()),
+        &[&maker],
+        ctx.svm.latest_blockhash(),
+    );
+    let simulated = ctx.svm.simulate_transaction(tx).unwrap();
+    let quote = TakeQuote::try_from_slice(&simulated.meta.return_data.data).unwrap();
+
+    assert_eq!(quote.amount_b_paid, 1_000_000);
+    assert_eq!(quote.amount_b_paid_ui, 1_051_271);
+    // mint_a has no UI scaling
+    assert_eq!(quote.amount_a_received_ui, quote.amount_a_received);
+}
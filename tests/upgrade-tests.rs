@@ -0,0 +1,204 @@
+use anchor_lang::{Discriminator, ToAccountMetas};
+use anchor_litesvm::{AnchorContext, AnchorLiteSVM};
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+/// Built by `scripts/build-v0-fixture.sh` from the original release
+const V0_PROGRAM: &str = "tests/fixtures/anchor_escrow_v0.so";
+
+/// `make` as the original release defined it. The discriminator is unchanged,
+/// but it took only `(seed, receive, amount)` and the first nine of today's
+/// `Make` accounts.
+fn make_v0_ix(
+    program_id: Pubkey,
+    maker: &Pubkey,
+    [seed, receive, amount]: [u64; 3],
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> Instruction {
+    let mut accounts = common::make_accounts(maker, seed, mint_a, mint_b, &spl_token::id())
+        .to_account_metas(None);
+    accounts.truncate(9);
+    let mut data = anchor_escrow::client::args::Make::DISCRIMINATOR.to_vec();
+    for arg in [seed, receive, amount] {
+        data.extend_from_slice(&arg.to_le_bytes());
+    }
+    Instruction { program_id, accounts, data }
+}
+
+struct UpgradedEscrow {
+    ctx: AnchorContext,
+    program_id: Pubkey,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    escrow_pda: Pubkey,
+}
+
+/// Makes an escrow with the old program, then deploys the current build over it
+fn setup() -> UpgradedEscrow {
+    let program_keypair = read_keypair_file("target/deploy/anchor_escrow-keypair.json").unwrap();
+    let program_id = program_keypair.pubkey();
+
+    let old_program = std::fs::read(V0_PROGRAM)
+        .expect("run scripts/build-v0-fixture.sh to build the v0 program");
+    let mut ctx = AnchorLiteSVM::build_with_program(program_id, &old_program);
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = ctx.svm.create_token_mint(&maker, 6).unwrap();
+    let mint_b = ctx.svm.create_token_mint(&maker, 6).unwrap();
+
+    let maker_ata_a = ctx.svm
+        .create_associated_token_account(&mint_a.pubkey(), &maker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_a.pubkey(), &maker_ata_a, &maker, 1_000)
+        .unwrap();
+
+    let taker_ata_b = ctx.svm
+        .create_associated_token_account(&mint_b.pubkey(), &taker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_b.pubkey(), &taker_ata_b, &maker, 2_000)
+        .unwrap();
+
+    let seed: u64 = 7;
+    let escrow_pda = ctx.svm.get_pda(
+        &[b"escrow", maker.pubkey().as_ref(), &seed.to_le_bytes()],
+        &program_id,
+    );
+
+    let make_ix = make_v0_ix(
+        program_id,
+        &maker.pubkey(),
+        [seed, 2_000, 1_000],
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+    );
+    ctx.execute_instruction(make_ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    // Upgrade in place: same program id, new bytes
+    ctx.svm.add_program(program_id, include_bytes!("../target/deploy/anchor_escrow.so"));
+    ctx.svm.expire_blockhash();
+
+    UpgradedEscrow {
+        ctx,
+        program_id,
+        maker,
+        taker,
+        mint_a: mint_a.pubkey(),
+        mint_b: mint_b.pubkey(),
+        escrow_pda,
+    }
+}
+
+fn migrate_ix(env: &UpgradedEscrow) -> Instruction {
+    env.ctx.program()
+        .accounts(anchor_escrow::client::accounts::MigrateEscrow {
+            maker: env.maker.pubkey(),
+            escrow: env.escrow_pda,
+            mint_a: env.mint_a,
+            mint_b: env.mint_b,
+            vault: get_associated_token_address(&env.escrow_pda, &env.mint_a),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            maker_state: common::maker_state(&env.maker.pubkey()),
+            pair_registry: common::pair_registry(&env.mint_a, &env.mint_b),
+            maker_registry: common::maker_registry(&env.maker.pubkey()),
+        })
+        .args(anchor_escrow::client::args::MigrateEscrow {})
+        .instruction()
+        .unwrap()
+}
+
+fn take_ix(env: &UpgradedEscrow) -> Instruction {
+    env.ctx.program()
+        .accounts(common::take_accounts(
+            &env.taker.pubkey(),
+            &env.maker.pubkey(),
+            &env.escrow_pda,
+            &env.mint_a,
+            &env.mint_b,
+            &spl_token::id(),
+        ))
+        .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+        .instruction()
+        .unwrap()
+}
+
+#[test]
+fn test_old_escrow_needs_migration() {
+    let mut env = setup();
+    let taker = env.taker.insecure_clone();
+
+    // The new build cannot read the old layout
+    let ix = take_ix(&env);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_anchor_error("AccountDidNotDeserialize");
+}
+
+#[test]
+fn test_migrate_then_take() {
+    let mut env = setup();
+    let maker = env.maker.insecure_clone();
+    let taker = env.taker.insecure_clone();
+
+    let old_len = env.ctx.svm.get_account(&env.escrow_pda).unwrap().data.len();
+    let maker_lamports = env.ctx.svm.get_balance(&env.maker.pubkey()).unwrap();
+
+    let ix = migrate_ix(&env);
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    // Grown to the current layout, with the maker covering the extra rent
+    // (and the fee)
+    let account = env.ctx.svm.get_account(&env.escrow_pda).unwrap();
+    assert!(account.data.len() > old_len);
+    assert_eq!(account.owner, env.program_id);
+    assert!(env.ctx.svm.get_balance(&env.maker.pubkey()).unwrap() < maker_lamports);
+
+    let escrow: anchor_escrow::accounts::Escrow = env.ctx.get_account(&env.escrow_pda).unwrap();
+    assert_eq!(escrow.version, 1);
+    // The deposit is read from the vault; the rest starts out fresh
+    assert_eq!(escrow.deposited, 1_000);
+    assert_eq!(escrow.filled, 0);
+    assert_eq!(escrow.receive, 2_000);
+    assert_eq!(escrow.seed, 7);
+    // The escrow predates the per-maker counter and is counted on migration
+    let maker_state: anchor_escrow::accounts::MakerState = env.ctx
+        .get_account(&common::maker_state(&env.maker.pubkey()))
+        .unwrap();
+    assert_eq!(maker_state.open_escrows, 1);
+
+    // Migrating twice is rejected
+    env.ctx.svm.expire_blockhash();
+    let ix = migrate_ix(&env);
+    env.ctx.execute_instruction(ix, &[&maker])
+        .unwrap()
+        .assert_anchor_error("EscrowAlreadyMigrated");
+
+    let ix = take_ix(&env);
+    env.ctx.execute_instruction(ix, &[&taker])
+        .unwrap()
+        .assert_success();
+    env.ctx.svm.assert_account_closed(&env.escrow_pda);
+}
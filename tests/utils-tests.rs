@@ -11,6 +11,8 @@ use litesvm_token::spl_token;
 // This creates the `anchor_escrow::client::accounts` and `anchor_escrow::client::args` modules
 anchor_lang::declare_program!(anchor_escrow);
 
+mod common;
+
 #[test]
 fn test_make_and_take_with_utils() {
     // ============================================================================
@@ -79,11 +81,19 @@ fn test_make_and_take_with_utils() {
             associated_token_program: spl_associated_token_account::id(),
             token_program: spl_token::id(),
             system_program: system_program::id(),
+            maker_state: common::maker_state(&maker.pubkey()),
+            pair_registry: common::pair_registry(&mint_a.pubkey(), &mint_b.pubkey()),
+            maker_registry: common::maker_registry(&maker.pubkey()),
+            config: common::config(),
+            mint_a_listing: common::mint_listing(&mint_a.pubkey()),
+            mint_b_listing: common::mint_listing(&mint_b.pubkey()),
         })
         .args(anchor_escrow::client::args::Make {
             seed,
             receive: 500_000_000,  // 0.5 tokens
             amount: 1_000_000_000, // 1 token
+            extensions: vec![],
+            accepted_hazards: 0,
         })
         .instruction()
         .unwrap();
@@ -114,14 +124,25 @@ fn test_make_and_take_with_utils() {
             mint_a: mint_a.pubkey(),
             mint_b: mint_b.pubkey(),
             vault,
-            taker_ata_a,
+            taker_ata_a: Some(taker_ata_a),
             taker_ata_b,
-            maker_ata_b,
+            maker_ata_b: Some(maker_ata_b),
             associated_token_program: spl_associated_token_account::id(),
             token_program: spl_token::id(),
             system_program: system_program::id(),
+            price_feed: None,
+            maker_state: common::maker_state(&maker.pubkey()),
+            pair_registry: common::pair_registry(&mint_a.pubkey(), &mint_b.pubkey()),
+            maker_registry: common::maker_registry(&maker.pubkey()),
+            config: common::config(),
+            memo_program: None,
+            maker_destination_b: None,
+            taker_destination_a: None,
+            referrer_b: None,
+            taker_gate: None,
+            taker_fill: None,
         })
-        .args(anchor_escrow::client::args::Take {})
+        .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
         .instruction()
         .unwrap();
 
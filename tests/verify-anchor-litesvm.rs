@@ -19,6 +19,8 @@ use sha2::{Sha256, Digest};
 // Generate client modules from the program using declare_program!
 anchor_lang::declare_program!(anchor_escrow);
 
+mod common;
+
 #[test]
 fn verify_type_safe_api() {
     println!("\n============================================================");
@@ -67,6 +69,12 @@ fn verify_type_safe_api() {
         associated_token_program: spl_associated_token_account::id(),
         token_program: spl_token::id(),
         system_program: system_program::id(),
+        maker_state: common::maker_state(&maker.pubkey()),
+        pair_registry: common::pair_registry(&mint_a.pubkey(), &mint_b.pubkey()),
+        maker_registry: common::maker_registry(&maker.pubkey()),
+        config: common::config(),
+        mint_a_listing: common::mint_listing(&mint_a.pubkey()),
+        mint_b_listing: common::mint_listing(&mint_b.pubkey()),
     };
 
     println!("✓ Type-safe account struct created (compiler-verified)");
@@ -85,6 +93,8 @@ fn verify_type_safe_api() {
         seed,
         receive: 500_000_000_u64,  // Type enforced by compiler
         amount: 1_000_000_000_u64, // Wrong type = compile error!
+        extensions: vec![],
+        accepted_hazards: 0,
     };
 
     println!("✓ Type-safe args struct created");
@@ -107,7 +117,7 @@ fn verify_type_safe_api() {
 
     println!("Expected discriminator (SHA256('global:make')[..8]):");
     println!("  {:?}", expected_discriminator);
-    println!("  Hex: {}", hex::encode(&expected_discriminator));
+    println!("  Hex: {}", hex::encode(expected_discriminator));
 
     // ============================================================================
     // VERIFICATION 4: Build Instruction Using Generated API
@@ -250,6 +260,12 @@ fn verify_account_order_independence() {
         maker_ata_a,
         escrow: escrow_pda,
         mint_a: mint_a.pubkey(),
+        maker_state: common::maker_state(&maker.pubkey()),
+        pair_registry: common::pair_registry(&mint_a.pubkey(), &mint_b.pubkey()),
+        maker_registry: common::maker_registry(&maker.pubkey()),
+        config: common::config(),
+        mint_a_listing: common::mint_listing(&mint_a.pubkey()),
+        mint_b_listing: common::mint_listing(&mint_b.pubkey()),
     };
 
     println!("✓ Accounts defined in non-sequential order");
@@ -261,6 +277,8 @@ fn verify_account_order_independence() {
             amount: 1_000_000_000,     // Order doesn't matter here either!
             seed,
             receive: 500_000_000,
+            extensions: vec![],
+            accepted_hazards: 0,
         })
         .instruction()
         .unwrap();
@@ -0,0 +1,264 @@
+//! Compute-unit comparison between the Borsh `Escrow` and the zero-copy
+//! `EscrowZeroCopy` paths for `take` and `refund`. Both sides run on a plain
+//! escrow (no extensions or oracle) of the same amounts, taken in full or
+//! refunded with every ATA already created and only that escrow in the
+//! registries, so they make the same CPIs. What remains is the account type
+//! and the checks the Borsh path runs on every escrow: extensions, price and
+//! status on take, status on refund. `make` is not compared, as the Borsh one
+//! also stores a price and an extension region.
+//!
+//! Run with `cargo test --test zero-copy-bench -- --nocapture` to see the
+//! numbers.
+
+use anchor_litesvm::AnchorContext;
+use litesvm_utils::{AssertionHelpers, TestHelpers};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use litesvm_token::spl_token;
+
+// Generate client modules from the program using declare_program!
+anchor_lang::declare_program!(anchor_escrow);
+
+mod common;
+
+struct Bench {
+    ctx: AnchorContext,
+    program_id: Pubkey,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+/// A maker and a taker funded for a few 1_000 mint_a for 2_000 mint_b
+/// escrows, with the ATAs either side is paid into already created
+fn setup() -> Bench {
+    let program_id = anchor_escrow::ID;
+    let mut ctx = common::load_program();
+
+    let maker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+    let taker = ctx.svm.create_funded_account(10_000_000_000).unwrap();
+
+    let mint_a = ctx.svm.create_token_mint(&maker, 6).unwrap();
+    let mint_b = ctx.svm.create_token_mint(&maker, 6).unwrap();
+
+    let maker_ata_a = ctx.svm
+        .create_associated_token_account(&mint_a.pubkey(), &maker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_a.pubkey(), &maker_ata_a, &maker, 10_000)
+        .unwrap();
+    let taker_ata_b = ctx.svm
+        .create_associated_token_account(&mint_b.pubkey(), &taker)
+        .unwrap();
+    ctx.svm
+        .mint_to(&mint_b.pubkey(), &taker_ata_b, &maker, 20_000)
+        .unwrap();
+    // Pre-create the destination ATAs so neither path pays for init_if_needed
+    ctx.svm
+        .create_associated_token_account(&mint_a.pubkey(), &taker)
+        .unwrap();
+    ctx.svm
+        .create_associated_token_account(&mint_b.pubkey(), &maker)
+        .unwrap();
+
+    Bench {
+        ctx,
+        program_id,
+        maker,
+        taker,
+        mint_a: mint_a.pubkey(),
+        mint_b: mint_b.pubkey(),
+    }
+}
+
+impl Bench {
+    fn escrow(&self, prefix: &[u8], seed: u64) -> (Pubkey, Pubkey) {
+        let escrow = self.ctx.svm.get_pda(
+            &[prefix, self.maker.pubkey().as_ref(), &seed.to_le_bytes()],
+            &self.program_id,
+        );
+        (escrow, get_associated_token_address(&escrow, &self.mint_a))
+    }
+
+    fn run(&mut self, ix: Instruction, signer: &Keypair) -> u64 {
+        let result = self.ctx.execute_instruction(ix, &[signer]).unwrap();
+        result.assert_success();
+        result.compute_units()
+    }
+
+    fn make(&mut self, seed: u64, zero_copy: bool) -> u64 {
+        let maker = self.maker.insecure_clone();
+        let ix = if zero_copy {
+            self.ctx.program()
+                .accounts(common::make_zero_copy_accounts(
+                    &maker.pubkey(),
+                    seed,
+                    &self.mint_a,
+                    &self.mint_b,
+                    &spl_token::id(),
+                ))
+                .args(anchor_escrow::client::args::MakeZeroCopy {
+                    seed,
+                    receive: 2_000,
+                    amount: 1_000,
+                    accepted_hazards: 0,
+                })
+                .instruction()
+                .unwrap()
+        } else {
+            self.ctx.program()
+                .accounts(common::make_accounts(&maker.pubkey(), seed, &self.mint_a, &self.mint_b, &spl_token::id()))
+                .args(anchor_escrow::client::args::Make {
+                    seed,
+                    receive: 2_000,
+                    amount: 1_000,
+                    extensions: vec![],
+                    accepted_hazards: 0,
+                })
+                .instruction()
+                .unwrap()
+        };
+        self.run(ix, &maker)
+    }
+
+    fn take(&mut self, seed: u64, zero_copy: bool) -> u64 {
+        let taker = self.taker.insecure_clone();
+        let taker_ata_a = get_associated_token_address(&taker.pubkey(), &self.mint_a);
+        let taker_ata_b = get_associated_token_address(&taker.pubkey(), &self.mint_b);
+        let maker_ata_b = get_associated_token_address(&self.maker.pubkey(), &self.mint_b);
+        let ix = if zero_copy {
+            let (escrow, vault) = self.escrow(b"escrow_zc", seed);
+            self.ctx.program()
+                .accounts(anchor_escrow::client::accounts::TakeZeroCopy {
+                    taker: taker.pubkey(),
+                    maker: self.maker.pubkey(),
+                    escrow,
+                    mint_a: self.mint_a,
+                    mint_b: self.mint_b,
+                    vault,
+                    taker_ata_a,
+                    taker_ata_b,
+                    maker_ata_b,
+                    associated_token_program: spl_associated_token_account::id(),
+                    token_program: spl_token::id(),
+                    system_program: system_program::id(),
+                    maker_state: common::maker_state(&self.maker.pubkey()),
+                    pair_registry: common::pair_registry(&self.mint_a, &self.mint_b),
+                    maker_registry: common::maker_registry(&self.maker.pubkey()),
+                    config: common::config(),
+                })
+                .args(anchor_escrow::client::args::TakeZeroCopy {})
+                .instruction()
+                .unwrap()
+        } else {
+            // Every optional account left out, as none is needed
+            self.ctx.program()
+                .accounts(common::take_accounts(
+                    &taker.pubkey(),
+                    &self.maker.pubkey(),
+                    &common::escrow(&self.maker.pubkey(), seed),
+                    &self.mint_a,
+                    &self.mint_b,
+                    &spl_token::id(),
+                ))
+                .args(anchor_escrow::client::args::Take { referral_bps: 0, allowlist: None })
+                .instruction()
+                .unwrap()
+        };
+        self.run(ix, &taker)
+    }
+
+    fn refund(&mut self, seed: u64, zero_copy: bool) -> u64 {
+        let maker = self.maker.insecure_clone();
+        let maker_ata_a = get_associated_token_address(&maker.pubkey(), &self.mint_a);
+        let ix = if zero_copy {
+            let (escrow, vault) = self.escrow(b"escrow_zc", seed);
+            self.ctx.program()
+                .accounts(anchor_escrow::client::accounts::RefundZeroCopy {
+                    maker: maker.pubkey(),
+                    escrow,
+                    mint_a: self.mint_a,
+                    vault,
+                    maker_ata_a,
+                    associated_token_program: spl_associated_token_account::id(),
+                    token_program: spl_token::id(),
+                    system_program: system_program::id(),
+                    maker_state: common::maker_state(&maker.pubkey()),
+                    pair_registry: common::pair_registry(&self.mint_a, &self.mint_b),
+                    maker_registry: common::maker_registry(&maker.pubkey()),
+                })
+                .args(anchor_escrow::client::args::RefundZeroCopy {})
+                .instruction()
+                .unwrap()
+        } else {
+            let (escrow, vault) = self.escrow(b"escrow", seed);
+            self.ctx.program()
+                .accounts(anchor_escrow::client::accounts::Refund {
+                    maker: maker.pubkey(),
+                    escrow,
+                    mint_a: self.mint_a,
+                    vault,
+                    maker_ata_a,
+                    associated_token_program: spl_associated_token_account::id(),
+                    token_program: spl_token::id(),
+                    system_program: system_program::id(),
+                    maker_state: common::maker_state(&maker.pubkey()),
+                    pair_registry: common::pair_registry(&self.mint_a, &self.mint_b),
+                    maker_registry: common::maker_registry(&maker.pubkey()),
+                })
+                .args(anchor_escrow::client::args::Refund {})
+                .instruction()
+                .unwrap()
+        };
+        self.run(ix, &maker)
+    }
+}
+
+#[test]
+fn bench_borsh_vs_zero_copy() {
+    let mut bench = setup();
+
+    // One escrow at a time, so every take and refund sees the same registries
+    let mut run = |zero_copy: bool| {
+        bench.make(1, zero_copy);
+        let take = bench.take(1, zero_copy);
+        bench.make(2, zero_copy);
+        let refund = bench.refund(2, zero_copy);
+        [take, refund]
+    };
+    let borsh = run(false);
+    let zero_copy = run(true);
+
+    println!("{:<8} {:>10} {:>10}", "ix", "borsh", "zero-copy");
+    println!("{:<8} {:>10} {:>10}", "take", borsh[0], zero_copy[0]);
+    println!("{:<8} {:>10} {:>10}", "refund", borsh[1], zero_copy[1]);
+
+    // Same CPIs on both sides, so skipping Borsh should never cost more
+    assert!(zero_copy[0] < borsh[0], "zero-copy take used more CUs than Borsh");
+    assert!(zero_copy[1] < borsh[1], "zero-copy refund used more CUs than Borsh");
+}
+
+#[test]
+fn test_zero_copy_round_trip() {
+    let mut bench = setup();
+    bench.make(7, true);
+
+    let (escrow_pda, vault) = bench.escrow(b"escrow_zc", 7);
+    let data = bench.ctx.svm.get_account(&escrow_pda).unwrap().data;
+    // One-byte discriminator followed by the `#[repr(C)]` fields
+    assert_eq!(data[0], 3);
+    assert_eq!(u64::from_le_bytes(data[1..9].try_into().unwrap()), 7);
+    bench.ctx.svm.assert_token_balance(&vault, 1_000);
+
+    bench.take(7, true);
+    let taker_ata_a = get_associated_token_address(&bench.taker.pubkey(), &bench.mint_a);
+    bench.ctx.svm.assert_token_balance(&taker_ata_a, 1_000);
+    bench.ctx.svm.assert_account_closed(&escrow_pda);
+    bench.ctx.svm.assert_account_closed(&vault);
+}